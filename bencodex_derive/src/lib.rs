@@ -0,0 +1,206 @@
+//! Derive macros converting structs from and into `bencodex::BNode`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, GenericArgument, PathArguments, Type};
+
+/// Generates `impl TryFrom<BNode>` for a struct with named fields.
+///
+/// Every field is looked up by its name, or the name given by
+/// `#[bencode(rename = "...")]`, and converted with `TryFrom<BNode>`.
+/// A missing key is an error unless the field is an `Option`.
+#[proc_macro_derive(FromBNode, attributes(bencode))]
+pub fn derive_from_bnode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_bnode(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generates `impl From<Struct> for BNode` producing a `BNode::Dict`.
+///
+/// Every field is converted with `BNode::from` and stored under its name, or
+/// the name given by `#[bencode(rename = "...")]`. `None` fields are omitted.
+#[proc_macro_derive(ToBNode, attributes(bencode))]
+pub fn derive_to_bnode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_bnode(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct Field<'a> {
+    ident: &'a syn::Ident,
+    key: String,
+    ty: &'a Type,
+    /// The inner type if the field is declared as `Option<T>`.
+    optional: Option<&'a Type>,
+}
+
+fn named_fields(input: &DeriveInput) -> syn::Result<Vec<Field<'_>>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "only structs with named fields are supported",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "only structs with named fields are supported",
+            ))
+        }
+    };
+
+    fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            let mut key = ident.to_string();
+
+            for attr in &field.attrs {
+                if !attr.path().is_ident("bencode") {
+                    continue;
+                }
+
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        let value: syn::LitStr = meta.value()?.parse()?;
+                        key = value.value();
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported bencode attribute"))
+                    }
+                })?;
+            }
+
+            Ok(Field {
+                ident,
+                key,
+                ty: &field.ty,
+                optional: option_inner(&field.ty),
+            })
+        })
+        .collect()
+}
+
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn expand_from_bnode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = named_fields(input)?.into_iter().map(|field| {
+        let Field {
+            ident,
+            key,
+            ty,
+            optional,
+        } = field;
+
+        match optional {
+            Some(inner) => quote! {
+                #ident: match dict.remove(#key) {
+                    ::core::option::Option::Some(value) => ::core::option::Option::Some(
+                        <#inner as ::core::convert::TryFrom<::bencodex::BNode>>::try_from(value)
+                            .map_err(|e| ::bencodex::__private::format!("field `{}`: {}", #key, e))?,
+                    ),
+                    ::core::option::Option::None => ::core::option::Option::None,
+                }
+            },
+            None => quote! {
+                #ident: match dict.remove(#key) {
+                    ::core::option::Option::Some(value) => {
+                        <#ty as ::core::convert::TryFrom<::bencodex::BNode>>::try_from(value)
+                            .map_err(|e| ::bencodex::__private::format!("field `{}`: {}", #key, e))?
+                    }
+                    ::core::option::Option::None => {
+                        return ::core::result::Result::Err(
+                            ::bencodex::__private::format!("missing key `{}`", #key),
+                        )
+                    }
+                }
+            },
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::TryFrom<::bencodex::BNode> for #name #ty_generics #where_clause {
+            type Error = ::bencodex::__private::String;
+
+            fn try_from(node: ::bencodex::BNode) -> ::core::result::Result<Self, Self::Error> {
+                #[allow(unused_mut)]
+                let mut dict = <::bencodex::BDict as ::core::convert::TryFrom<::bencodex::BNode>>::try_from(node)?;
+
+                ::core::result::Result::Ok(Self {
+                    #(#fields,)*
+                })
+            }
+        }
+    })
+}
+
+fn expand_to_bnode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // `BDict` is a `BTreeMap`, so the keys end up in canonical order no matter
+    // how the fields are declared.
+    let inserts = named_fields(input)?.into_iter().map(|field| {
+        let Field {
+            ident,
+            key,
+            optional,
+            ..
+        } = field;
+
+        match optional {
+            Some(_) => quote! {
+                if let ::core::option::Option::Some(value) = value.#ident {
+                    dict.insert(
+                        ::bencodex::__private::String::from(#key),
+                        ::bencodex::BNode::from(value),
+                    );
+                }
+            },
+            None => quote! {
+                dict.insert(
+                    ::bencodex::__private::String::from(#key),
+                    ::bencodex::BNode::from(value.#ident),
+                );
+            },
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for ::bencodex::BNode #where_clause {
+            fn from(value: #name #ty_generics) -> Self {
+                #[allow(unused_mut)]
+                let mut dict = ::bencodex::BDict::new();
+                #(#inserts)*
+                ::bencodex::BNode::Dict(dict)
+            }
+        }
+    })
+}
@@ -0,0 +1,108 @@
+use bencodex::{parse_from_str, BNode};
+use bencodex_derive::{FromBNode, ToBNode};
+
+#[derive(Debug, PartialEq, FromBNode, ToBNode)]
+struct TorrentInfo {
+    name: String,
+    length: i64,
+    #[bencode(rename = "piece length")]
+    piece_length: i64,
+    pieces: Vec<u8>,
+    private: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, FromBNode, ToBNode)]
+struct Torrent {
+    announce: String,
+    #[bencode(rename = "announce-list")]
+    announce_list: Option<Vec<BNode>>,
+    info: TorrentInfo,
+}
+
+#[test]
+fn test_from_bnode() {
+    let bnode = parse_from_str(
+        "d8:announce9:tracker:14:infod6:lengthi1024e4:name8:file.iso12:piece lengthi256e6:pieces2:abee",
+    )
+    .unwrap();
+
+    let torrent = Torrent::try_from(bnode).unwrap();
+    assert_eq!(
+        Torrent {
+            announce: "tracker:1".to_string(),
+            announce_list: None,
+            info: TorrentInfo {
+                name: "file.iso".to_string(),
+                length: 1024,
+                piece_length: 256,
+                pieces: b"ab".to_vec(),
+                private: None,
+            },
+        },
+        torrent
+    );
+}
+
+#[test]
+fn test_from_bnode_optional_present() {
+    let bnode =
+        parse_from_str("d6:lengthi1e4:name1:a12:piece lengthi1e6:pieces0:7:privatei1ee").unwrap();
+
+    let info: TorrentInfo = bnode.try_into().unwrap();
+    assert_eq!(Some(1), info.private);
+}
+
+#[test]
+fn test_from_bnode_errors() {
+    let err = TorrentInfo::try_from(parse_from_str("d4:name1:ae").unwrap()).unwrap_err();
+    assert_eq!("missing key `length`", err);
+
+    let bnode = parse_from_str("d6:length1:x4:name1:a12:piece lengthi1e6:pieces0:e").unwrap();
+    let err = TorrentInfo::try_from(bnode).unwrap_err();
+    assert_eq!("field `length`: expected integer, found bytes", err);
+
+    let err = TorrentInfo::try_from(BNode::Integer(1)).unwrap_err();
+    assert_eq!("expected dict, found integer", err);
+}
+
+#[test]
+fn test_to_bnode() {
+    let info = TorrentInfo {
+        name: "file.iso".to_string(),
+        length: 1024,
+        piece_length: 256,
+        pieces: b"ab".to_vec(),
+        private: None,
+    };
+
+    assert_eq!(
+        b"d6:lengthi1024e4:name8:file.iso12:piece lengthi256e6:pieces2:abe".to_vec(),
+        BNode::from(info).to_bytes()
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let torrent = Torrent {
+        announce: "tracker:1".to_string(),
+        announce_list: Some(vec![BNode::from("tracker:2")]),
+        info: TorrentInfo {
+            name: "file.iso".to_string(),
+            length: 1024,
+            piece_length: 256,
+            pieces: b"ab".to_vec(),
+            private: Some(1),
+        },
+    };
+
+    let bnode = BNode::from(torrent);
+    let raw = bnode.to_bytes();
+    assert_eq!(
+        bnode,
+        parse_from_str(std::str::from_utf8(&raw).unwrap()).unwrap()
+    );
+
+    let torrent = Torrent::try_from(bnode).unwrap();
+    assert_eq!(Some(1), torrent.info.private);
+    assert_eq!(Some(vec![BNode::from("tracker:2")]), torrent.announce_list);
+}
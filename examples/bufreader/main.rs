@@ -1,42 +1,21 @@
-use std::{
-    env,
-    fs::File,
-    io::{BufReader, Bytes, Read},
-};
+use std::{env, error::Error, fs::File, io::BufReader};
 
-struct Adapter {
-    bytes: Bytes<BufReader<File>>,
-}
-
-impl Iterator for Adapter {
-    type Item = u8;
-    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
-        match self.bytes.next() {
-            Some(Ok(v)) => Some(v),
-            _ => None,
-        }
-    }
-}
-
-fn main() {
+fn main() -> Result<(), Box<dyn Error>> {
     let f = File::open(
-        env::current_dir()
-            .unwrap()
+        env::current_dir()?
             .join("examples")
             .join("bufreader")
             .join("bufreader-test.torrent"),
-    )
-    .unwrap();
+    )?;
     let reader = BufReader::new(f);
 
-    let mut adapter = Adapter {
-        bytes: reader.bytes(),
-    };
-    let bnode = bencodex::parse(&mut adapter).unwrap();
-    let dict = bnode.as_dict().unwrap();
+    let bnode = bencodex::parse_from_read(reader)?;
+    let dict = bnode.as_dict()?;
     assert_eq!(
-        dict.get("bar").unwrap().as_bytes().unwrap(),
+        dict.get("bar").ok_or("missing `bar`")?.as_bytes()?,
         "spam".as_bytes()
     );
-    assert_eq!(dict.get("foo").unwrap().as_integer().unwrap(), &42);
+    assert_eq!(dict.get("foo").ok_or("missing `foo`")?.as_integer()?, &42);
+
+    Ok(())
 }
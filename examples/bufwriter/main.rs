@@ -1,7 +1,7 @@
-use bencodex::{BDict, BNode};
+use bencodex::{BDict, BNode, IoResult};
 use std::{env, fs::File};
 
-fn main() {
+fn main() -> IoResult<()> {
     let mut dict = BDict::new();
     dict.insert("bar".to_string(), "spam".into());
     dict.insert("foo".to_string(), 42.into());
@@ -9,12 +9,12 @@ fn main() {
     let bnode: BNode = dict.into();
 
     let mut file = File::create(
-        env::current_dir()
-            .unwrap()
+        env::current_dir()?
             .join("examples")
             .join("bufwriter")
             .join("bufwriter-test.torrent"),
-    )
-    .unwrap();
-    bnode.serialize(&mut file).unwrap();
+    )?;
+    bnode.serialize(&mut file)?;
+
+    Ok(())
 }
@@ -1,15 +1,20 @@
-fn main() {
+use bencodex::BNode;
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
     let b = "d3:inti233e3:lstl7:bencodeee";
-    let node = bencodex::parse(&mut b.bytes()).unwrap();
+    let node: BNode = b.parse()?;
 
-    let dict = node.as_dict().unwrap();
-    let int = dict.get("int").unwrap().as_integer().unwrap();
+    let dict = node.as_dict()?;
+    let int = dict.get("int").ok_or("missing `int`")?.as_integer()?;
 
     assert_eq!(int, &233);
-    let list = dict.get("lst").unwrap().as_list().unwrap();
+    let list = dict.get("lst").ok_or("missing `lst`")?.as_list()?;
     assert_eq!(list.len(), 1);
     assert_eq!(
-        list.get(0).unwrap().as_bytes().unwrap(),
+        list.first().ok_or("empty `lst`")?.as_bytes()?,
         "bencode".as_bytes()
     );
+
+    Ok(())
 }
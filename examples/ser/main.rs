@@ -1,6 +1,7 @@
 use bencodex::{BDict, BNode};
+use std::error::Error;
 
-fn main() {
+fn main() -> Result<(), Box<dyn Error>> {
     let mut dict = BDict::new();
     dict.insert("bar".to_string(), "spam".into());
     dict.insert("foo".to_string(), 42.into());
@@ -8,6 +9,8 @@ fn main() {
     let bnode: BNode = dict.into();
 
     let mut buf = vec![];
-    bnode.serialize(&mut buf).unwrap();
-    assert_eq!("d3:bar4:spam3:fooi42ee".as_bytes(), buf)
+    bnode.serialize(&mut buf)?;
+    assert_eq!("d3:bar4:spam3:fooi42ee".as_bytes(), buf);
+
+    Ok(())
 }
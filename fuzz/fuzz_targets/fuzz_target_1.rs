@@ -0,0 +1,14 @@
+#![no_main]
+
+use bencodex::parse_from_slice;
+use libfuzzer_sys::fuzz_target;
+
+// Parsing must never panic, and anything which parses must survive a
+// round-trip through its encoding. Errors own no resources besides their
+// message, which leak checks of the sanitizer would report.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(node) = parse_from_slice(data) {
+        let reparsed = parse_from_slice(&node.to_bytes()).expect("encoding should parse");
+        assert_eq!(node, reparsed);
+    }
+});
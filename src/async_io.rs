@@ -0,0 +1,80 @@
+use crate::prelude::*;
+use crate::reader::{Builder, Reader};
+use crate::{BNode, BNodeSink, Error, ErrorKind, Lexer, ParserConfig, Result};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const CHUNK_SIZE: usize = 4096;
+
+/// Whether `input` starts with a whole token along with the bytes of a byte
+/// string, so the parser only runs out of input at the end of the stream.
+/// Malformed input counts as whole, for the parser to report it.
+fn has_item(input: &[u8]) -> bool {
+    match input.first() {
+        None => false,
+        Some(b'0'..=b'9') => match input.iter().position(|b| !b.is_ascii_digit()) {
+            Some(colon) if input[colon] == b':' => {
+                let len = core::str::from_utf8(&input[..colon])
+                    .ok()
+                    .and_then(|len| len.parse::<usize>().ok());
+                match len {
+                    Some(len) => input[colon + 1..].len() >= len,
+                    None => true,
+                }
+            }
+            Some(_) => true,
+            None => false,
+        },
+        Some(b'i') => input[1..].iter().any(|b| !b.is_ascii_digit() && *b != b'-'),
+        Some(_) => true,
+    }
+}
+
+/// Parses the first value of `reader`, reading it in chunks of 4096 bytes as
+/// the parser needs them.
+///
+/// Parsing stops once the value is complete, so the reader doesn't have to
+/// end after it, e.g. a connection kept open. Bytes read past the value are
+/// dropped. The errors are the ones of [`parse_partial`](crate::parse_partial),
+/// a failed read is reported as [`ErrorKind::Io`] at the offset reached so far.
+pub async fn parse_async<R>(mut reader: R) -> Result<BNode>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = Vec::with_capacity(CHUNK_SIZE);
+    // The offset of `buf` into the stream, and the bytes of it parsed so far.
+    let mut offset = 0u64;
+    let mut parsed = 0;
+    let mut eof = false;
+
+    let mut parser = Reader::new(ParserConfig::default());
+    let mut builder = Builder::new();
+    let mut sink = BNodeSink::default();
+
+    loop {
+        while !eof && !has_item(&buf[parsed..]) {
+            buf.drain(..parsed);
+            offset += parsed as u64;
+            parsed = 0;
+
+            buf.reserve(CHUNK_SIZE);
+            match reader.read_buf(&mut buf).await {
+                Ok(0) => eof = true,
+                Ok(_) => {}
+                Err(e) => {
+                    let position = offset + buf.len() as u64;
+                    return Err(Error::new(ErrorKind::Io(e.to_string()), position));
+                }
+            }
+        }
+
+        let mut stream = buf[parsed..].iter().copied();
+        let mut lexer = Lexer::new(&mut stream);
+        lexer.consumed = offset + parsed as u64;
+        let node = builder.step(&mut parser, &mut lexer, &mut sink)?;
+        parsed = (lexer.consumed - offset) as usize;
+
+        if let Some(node) = node {
+            return Ok(node);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use crate::prelude::*;
+use crate::reader::{read_single, NodeSink, Strings};
+use crate::{BDict, BNode, Lexer, Result};
+use alloc::collections::BTreeMap;
+use core::ops::Range;
+
+/// A parsed node whose byte strings and dictionary keys borrow from the
+/// input instead of being copied.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BorrowedBNode<'a> {
+    Integer(i64),
+    #[cfg(feature = "bigint")]
+    BigInteger(Box<num_bigint::BigInt>),
+    Bytes(&'a [u8]),
+    List(Vec<BorrowedBNode<'a>>),
+    Dict(BTreeMap<&'a str, BorrowedBNode<'a>>),
+}
+
+enum OwnedWork<'a> {
+    Convert(BorrowedBNode<'a>),
+    /// Collects the given number of converted elements into a list.
+    List(usize),
+    /// Collects as many converted values as there are keys into a dictionary.
+    Dict(Vec<&'a str>),
+}
+
+impl BorrowedBNode<'_> {
+    pub fn into_owned(self) -> BNode {
+        // Pending conversions are kept on the heap instead of the call stack,
+        // so deeply nested nodes cannot overflow it.
+        let mut work = vec![OwnedWork::Convert(self)];
+        let mut converted: Vec<BNode> = vec![];
+
+        while let Some(item) = work.pop() {
+            match item {
+                OwnedWork::Convert(BorrowedBNode::Integer(i)) => converted.push(BNode::Integer(i)),
+                #[cfg(feature = "bigint")]
+                OwnedWork::Convert(BorrowedBNode::BigInteger(i)) => {
+                    converted.push(BNode::BigInteger(i))
+                }
+                OwnedWork::Convert(BorrowedBNode::Bytes(bytes)) => converted.push(bytes.into()),
+                OwnedWork::Convert(BorrowedBNode::List(list)) => {
+                    work.push(OwnedWork::List(list.len()));
+                    work.extend(list.into_iter().rev().map(OwnedWork::Convert));
+                }
+                OwnedWork::Convert(BorrowedBNode::Dict(dict)) => {
+                    let (keys, values): (Vec<_>, Vec<_>) = dict.into_iter().unzip();
+                    work.push(OwnedWork::Dict(keys));
+                    work.extend(values.into_iter().rev().map(OwnedWork::Convert));
+                }
+                OwnedWork::List(len) => {
+                    let list = converted.split_off(converted.len() - len);
+                    converted.push(list.into());
+                }
+                OwnedWork::Dict(keys) => {
+                    let values = converted.split_off(converted.len() - keys.len());
+                    let dict: BDict = keys.into_iter().map(str::to_string).zip(values).collect();
+                    converted.push(dict.into());
+                }
+            }
+        }
+
+        converted.pop().unwrap()
+    }
+}
+
+enum Frame<'a> {
+    List(Vec<BorrowedBNode<'a>>),
+    Dict(BTreeMap<&'a str, BorrowedBNode<'a>>, Option<&'a str>),
+}
+
+/// Points into the input instead of copying byte strings.
+struct BorrowedSink<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Strings for BorrowedSink<'a> {
+    type Bytes = &'a [u8];
+    type Key = &'a str;
+
+    fn read_bytes<T>(&mut self, lexer: &mut Lexer<'_, T>, len: usize) -> Result<&'a [u8]>
+    where
+        T: Iterator<Item = u8>,
+    {
+        let start = lexer.consumed as usize;
+        lexer.skip_bytes(len)?;
+
+        Ok(&self.input[start..start + len])
+    }
+
+    fn make_key(&mut self, bytes: &'a [u8]) -> Option<&'a str> {
+        core::str::from_utf8(bytes).ok()
+    }
+}
+
+impl<'a> NodeSink for BorrowedSink<'a> {
+    type Node = BorrowedBNode<'a>;
+    type Frame = Frame<'a>;
+
+    fn integer(&mut self, value: i64, _: Range<u64>) -> BorrowedBNode<'a> {
+        BorrowedBNode::Integer(value)
+    }
+
+    #[cfg(feature = "bigint")]
+    fn big_integer(&mut self, value: Box<num_bigint::BigInt>, _: Range<u64>) -> BorrowedBNode<'a> {
+        BorrowedBNode::BigInteger(value)
+    }
+
+    fn bytes(&mut self, bytes: &'a [u8], _: Range<u64>) -> BorrowedBNode<'a> {
+        BorrowedBNode::Bytes(bytes)
+    }
+
+    fn list(&mut self, _: u64) -> Frame<'a> {
+        Frame::List(vec![])
+    }
+
+    fn dict(&mut self, _: u64) -> Frame<'a> {
+        Frame::Dict(BTreeMap::new(), None)
+    }
+
+    fn key(&mut self, frame: &mut Frame<'a>, key: &'a str) {
+        if let Frame::Dict(_, pending) = frame {
+            *pending = Some(key);
+        }
+    }
+
+    fn push(&mut self, frame: &mut Frame<'a>, node: BorrowedBNode<'a>) {
+        match frame {
+            Frame::List(list) => list.push(node),
+            Frame::Dict(dict, key) => {
+                dict.insert(key.take().unwrap(), node);
+            }
+        }
+    }
+
+    fn end(&mut self, frame: Frame<'a>, _: u64) -> BorrowedBNode<'a> {
+        match frame {
+            Frame::List(list) => BorrowedBNode::List(list),
+            Frame::Dict(dict, _) => BorrowedBNode::Dict(dict),
+        }
+    }
+}
+
+/// Parses `input` without copying byte strings, see [`BorrowedBNode`].
+pub fn parse_borrowed(input: &[u8]) -> Result<BorrowedBNode<'_>> {
+    let mut stream = input.iter().copied();
+    read_single(&mut Lexer::new(&mut stream), &mut BorrowedSink { input })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_borrowed, BorrowedBNode};
+    use crate::{parse_from_slice, BNode};
+
+    #[test]
+    fn test_parse_borrowed() {
+        let raw = b"d3:bar4:spam3:fooli42e0:ee";
+        let bnode = parse_borrowed(raw).unwrap();
+
+        match &bnode {
+            BorrowedBNode::Dict(dict) => {
+                assert_eq!(Some(&BorrowedBNode::Bytes(b"spam")), dict.get("bar"));
+                assert_eq!(
+                    Some(&BorrowedBNode::List(vec![
+                        BorrowedBNode::Integer(42),
+                        BorrowedBNode::Bytes(b"")
+                    ])),
+                    dict.get("foo")
+                );
+            }
+            _ => panic!("should be a dictionary"),
+        }
+
+        assert_eq!(parse_from_slice(raw).unwrap(), bnode.into_owned());
+    }
+
+    #[test]
+    fn test_parse_borrowed_shares_input() {
+        let raw = b"l4:spame";
+        match parse_borrowed(raw).unwrap() {
+            BorrowedBNode::List(list) => match list[0] {
+                BorrowedBNode::Bytes(bytes) => assert_eq!(raw[3..7].as_ptr(), bytes.as_ptr()),
+                _ => panic!("should be bytes"),
+            },
+            _ => panic!("should be a list"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_parse_borrowed_big_integer() {
+        let raw = b"li99999999999999999999ee";
+        let bnode = parse_borrowed(raw).unwrap();
+
+        assert_eq!(parse_from_slice(raw).unwrap(), bnode.into_owned());
+    }
+
+    #[test]
+    fn test_into_owned_deeply_nested() {
+        let depth = 5000;
+        let raw = "l".repeat(depth) + &"e".repeat(depth);
+
+        let mut node = parse_borrowed(raw.as_bytes()).unwrap().into_owned();
+        for _ in 1..depth {
+            node = node.into_list().unwrap().pop().unwrap();
+        }
+        assert_eq!(BNode::List(vec![]), node);
+    }
+
+    #[test]
+    fn test_parse_borrowed_failed() {
+        let cases: [&[u8]; 6] = [
+            b"",
+            b"5:halo",
+            b"i1ei2e",
+            b"l4:halo",
+            b"d1:\xffi1ee",
+            b"di1ei2ee",
+        ];
+        for x in &cases {
+            if parse_borrowed(x).is_ok() {
+                panic!("{:?} should fail", x);
+            }
+        }
+    }
+}
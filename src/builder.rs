@@ -0,0 +1,79 @@
+use crate::prelude::*;
+use crate::{BDict, BList, BNode};
+
+/// Builds a `BNode::List` by chaining [`BListBuilder::push`].
+#[derive(Debug, Default, Clone)]
+pub struct BListBuilder {
+    list: BList,
+}
+
+impl BListBuilder {
+    pub fn new() -> BListBuilder {
+        BListBuilder::default()
+    }
+
+    pub fn push(mut self, item: impl Into<BNode>) -> Self {
+        self.list.push(item.into());
+        self
+    }
+
+    pub fn build(self) -> BNode {
+        BNode::List(self.list)
+    }
+}
+
+/// Builds a `BNode::Dict` by chaining [`BDictBuilder::insert`], keys end up
+/// sorted no matter the order they are inserted in.
+#[derive(Debug, Default, Clone)]
+pub struct BDictBuilder {
+    dict: BDict,
+}
+
+impl BDictBuilder {
+    pub fn new() -> BDictBuilder {
+        BDictBuilder::default()
+    }
+
+    /// Inserts an entry, replacing the value of a key inserted before.
+    pub fn insert(mut self, key: impl Into<String>, value: impl Into<BNode>) -> Self {
+        self.dict.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> BNode {
+        BNode::Dict(self.dict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BDictBuilder, BListBuilder};
+    use crate::BNode;
+
+    #[test]
+    fn test_list_builder() {
+        let list = BListBuilder::new().push(1).push("spam").push(true).build();
+        assert_eq!("li1e4:spami1ee", list.to_string());
+        assert_eq!(BNode::List(vec![]), BListBuilder::new().build());
+    }
+
+    #[test]
+    fn test_dict_builder() {
+        let torrent = BDictBuilder::new()
+            .insert(
+                "info",
+                BDictBuilder::new().insert("name", "file.iso").build(),
+            )
+            .insert("announce", "http://a")
+            .insert("seeds", BListBuilder::new().push("x").build())
+            .build();
+
+        assert_eq!(
+            "d8:announce8:http://a4:infod4:name8:file.isoe5:seedsl1:xee",
+            torrent.to_string()
+        );
+
+        let dict = BDictBuilder::new().insert("a", 1).insert("a", 2).build();
+        assert_eq!("d1:ai2ee", dict.to_string());
+    }
+}
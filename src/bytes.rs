@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use crate::BNode;
+use core::ops::Deref;
+
+/// The content of a `BNode::Bytes`, usable wherever `&[u8]` is expected.
+#[repr(transparent)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Default)]
+pub struct BBytes(Vec<u8>);
+
+impl BBytes {
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for BBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl core::borrow::Borrow<[u8]> for BBytes {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Deref for BBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for BBytes {
+    fn from(value: Vec<u8>) -> Self {
+        BBytes(value)
+    }
+}
+
+impl From<&[u8]> for BBytes {
+    fn from(value: &[u8]) -> Self {
+        BBytes(value.into())
+    }
+}
+
+impl From<BBytes> for BNode {
+    fn from(value: BBytes) -> Self {
+        BNode::Bytes(value.0)
+    }
+}
+
+impl TryFrom<BNode> for BBytes {
+    type Error = String;
+
+    fn try_from(value: BNode) -> Result<Self, Self::Error> {
+        Vec::<u8>::try_from(value).map(BBytes)
+    }
+}
+
+impl BNode {
+    /// Borrows the content of a `BNode::Bytes` as [`BBytes`].
+    pub fn as_bytes_ref(&self) -> Option<&BBytes> {
+        match self {
+            // SAFETY: `BBytes` is a `repr(transparent)` wrapper of `Vec<u8>`.
+            BNode::Bytes(bytes) => Some(unsafe { &*(bytes as *const Vec<u8> as *const BBytes) }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BBytes;
+    use crate::BNode;
+
+    fn len_of(bytes: impl AsRef<[u8]>) -> usize {
+        bytes.as_ref().len()
+    }
+
+    #[test]
+    fn test_bbytes_as_ref() {
+        let bnode = BNode::from("spam");
+        let bytes = bnode.as_bytes_ref().unwrap();
+
+        assert_eq!(4, len_of(bytes));
+        assert_eq!(b"spam", &bytes[..]);
+        assert!(bytes.starts_with(b"sp"));
+        assert_eq!(None, BNode::Integer(1).as_bytes_ref());
+    }
+
+    #[test]
+    fn test_bbytes_conversions() {
+        let bytes = BBytes::try_from(BNode::from("spam")).unwrap();
+        assert_eq!(BBytes::from(b"spam".as_slice()), bytes);
+        assert_eq!(BNode::from("spam"), BNode::from(bytes.clone()));
+        assert_eq!(b"spam".to_vec(), bytes.into_vec());
+
+        assert_eq!(
+            Err("expected bytes, found integer".to_string()),
+            BBytes::try_from(BNode::Integer(1))
+        );
+    }
+}
@@ -0,0 +1,708 @@
+use crate::prelude::*;
+use crate::{BDict, BNode, BNodeType};
+use alloc::collections::btree_map;
+
+enum MergeWork {
+    Merge(BNode, BNode),
+    /// Puts the merged values of the given shared keys back into a dictionary.
+    Assemble(BNode, Vec<String>),
+}
+
+impl BNode {
+    /// Inserts into a dictionary, returning the value previously stored under
+    /// `key`.
+    pub fn insert_entry(
+        &mut self,
+        key: String,
+        value: BNode,
+    ) -> core::result::Result<Option<BNode>, String> {
+        match self {
+            BNode::Dict(dict) => Ok(dict.insert(key, value)),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Ok(dict.insert(key, value)),
+            node => Err(node.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    /// Removes from a dictionary, an ordered one keeps the order of the
+    /// remaining keys.
+    pub fn remove_entry(&mut self, key: &str) -> core::result::Result<Option<BNode>, String> {
+        match self {
+            BNode::Dict(dict) => Ok(dict.remove(key)),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Ok(dict.shift_remove(key)),
+            node => Err(node.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    pub fn get_entry_mut(&mut self, key: &str) -> core::result::Result<Option<&mut BNode>, String> {
+        match self {
+            BNode::Dict(dict) => Ok(dict.get_mut(key)),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Ok(dict.get_mut(key)),
+            node => Err(node.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    /// Exposes [`BTreeMap::entry`](alloc::collections::BTreeMap::entry) of a
+    /// dictionary, which fails for an ordered one like [`BNode::as_dict_mut`].
+    pub fn entry(
+        &mut self,
+        key: String,
+    ) -> core::result::Result<btree_map::Entry<'_, String, BNode>, String> {
+        Ok(self.as_dict_mut()?.entry(key))
+    }
+
+    /// Moves the value stored under `old` to `new`, overwriting any value
+    /// already stored there. In an ordered dictionary the value takes the
+    /// position of `new` if it exists and of `old` otherwise.
+    pub fn rename_key(&mut self, old: &str, new: String) -> core::result::Result<(), String> {
+        match self {
+            BNode::Dict(dict) => {
+                let value = dict.remove(old).ok_or("key not found")?;
+                dict.insert(new, value);
+            }
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => {
+                let index = dict.get_index_of(old).ok_or("key not found")?;
+                match dict.get_index_of(new.as_str()) {
+                    Some(existing) if existing == index => {}
+                    Some(_) => {
+                        let (_, value) = dict.shift_remove_index(index).unwrap();
+                        dict.insert(new, value);
+                    }
+                    None => {
+                        let _ = dict.replace_index(index, new);
+                    }
+                }
+            }
+            node => return Err(node.type_mismatch(BNodeType::Dict)),
+        }
+        Ok(())
+    }
+
+    /// Returns `false` for anything but a dictionary.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Looks up `key`, `None` if it's missing or `self` is not a dictionary.
+    pub fn get(&self, key: &str) -> Option<&BNode> {
+        match self {
+            BNode::Dict(dict) => dict.get(key),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => dict.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut BNode> {
+        match self {
+            BNode::Dict(dict) => dict.get_mut(key),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => dict.get_mut(key),
+            _ => None,
+        }
+    }
+
+    /// Like [`BNode::get`], but falls back to `default` instead of `None`.
+    pub fn get_or_default<'a>(&'a self, key: &str, default: &'a BNode) -> &'a BNode {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Returns the value stored under `key`, inserting `default` first if
+    /// it's missing.
+    pub fn get_or_insert_default(
+        &mut self,
+        key: String,
+        default: BNode,
+    ) -> core::result::Result<&mut BNode, String> {
+        match self {
+            BNode::Dict(dict) => Ok(dict.entry(key).or_insert(default)),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Ok(dict.entry(key).or_insert(default)),
+            node => Err(node.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    /// Transforms every value of a dictionary, keeping the keys.
+    pub fn map_values<F>(self, f: F) -> core::result::Result<BNode, String>
+    where
+        F: Fn(BNode) -> BNode,
+    {
+        match self {
+            BNode::Dict(dict) => Ok(dict
+                .into_iter()
+                .map(|(k, v)| (k, f(v)))
+                .collect::<BDict>()
+                .into()),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Ok(BNode::OrderedDict(
+                dict.into_iter().map(|(k, v)| (k, f(v))).collect(),
+            )),
+            node => Err(node.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    /// Like [`BNode::map_values`], but values which are dictionaries themselves
+    /// are recursed into instead of being passed to `f`.
+    pub fn map_values_recursive<F>(self, f: F) -> core::result::Result<BNode, String>
+    where
+        F: Fn(BNode) -> BNode,
+    {
+        if !self.is_dict() {
+            return Err(self.type_mismatch(BNodeType::Dict));
+        }
+
+        // Pending values are kept on the heap instead of the call stack, so
+        // deeply nested nodes cannot overflow it.
+        let mut root = self;
+        let mut stack = vec![&mut root];
+        while let Some(node) = stack.pop() {
+            match node {
+                BNode::Dict(dict) => stack.extend(dict.values_mut().rev()),
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => stack.extend(dict.values_mut().rev()),
+                node => *node = f(core::mem::take(node)),
+            }
+        }
+
+        Ok(root)
+    }
+
+    /// Copies the entries of a dictionary whose key satisfies `predicate`.
+    pub fn filter_keys<F>(&self, predicate: F) -> core::result::Result<BNode, String>
+    where
+        F: Fn(&str) -> bool,
+    {
+        match self {
+            BNode::Dict(dict) => Ok(dict
+                .iter()
+                .filter(|(k, _)| predicate(k))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<BDict>()
+                .into()),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Ok(BNode::OrderedDict(
+                dict.iter()
+                    .filter(|(k, _)| predicate(k))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            )),
+            node => Err(node.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    /// Clones a dictionary without the entry under `key`, which must exist.
+    pub fn clone_and_remove_key(&self, key: &str) -> core::result::Result<BNode, String> {
+        if !self.is_dict() {
+            return Err(self.type_mismatch(BNodeType::Dict));
+        }
+        if !self.contains_key(key) {
+            return Err("key not found".into());
+        }
+
+        self.filter_keys(|k| k != key)
+    }
+
+    /// Clones a dictionary keeping only the entries under `keys`, keys which
+    /// don't exist are ignored.
+    pub fn clone_and_keep_keys(&self, keys: &[&str]) -> core::result::Result<BNode, String> {
+        self.filter_keys(|k| keys.contains(&k))
+    }
+
+    /// Merges two dictionaries, values of `other` win on conflicting keys. The
+    /// result is ordered if `self` is, with the new keys of `other` last.
+    pub fn merge_dict(self, other: BNode) -> core::result::Result<BNode, String> {
+        if !self.is_dict() {
+            return Err(self.type_mismatch(BNodeType::Dict));
+        }
+        let other = other.into_dict_iter()?;
+
+        match self {
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(mut dict) => {
+                dict.extend(other);
+                Ok(BNode::OrderedDict(dict))
+            }
+            node => {
+                let mut dict = node.into_dict().unwrap();
+                dict.extend(other);
+                Ok(dict.into())
+            }
+        }
+    }
+
+    /// Merges two dictionaries, values of `self` win on conflicting keys.
+    pub fn merge_dict_self_wins(self, other: BNode) -> core::result::Result<BNode, String> {
+        other.merge_dict(self)
+    }
+
+    /// Rewrites every ordered dictionary in the tree into a sorted one, so the
+    /// node compares equal to its canonical encoding parsed back. Does nothing
+    /// without the `indexmap` feature, as every other node is canonical
+    /// already.
+    pub fn normalize(&mut self) {
+        // Pending nodes are kept on the heap instead of the call stack, so
+        // deeply nested nodes cannot overflow it.
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            #[cfg(feature = "indexmap")]
+            if let BNode::OrderedDict(dict) = node {
+                *node = BNode::Dict(core::mem::take(dict).into_iter().collect());
+            }
+
+            match node {
+                BNode::List(list) => stack.extend(list.iter_mut()),
+                BNode::Dict(dict) => stack.extend(dict.values_mut()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Recursively merges `other` into `self`.
+    ///
+    /// * Two dictionaries are merged key by key, recursing into shared keys.
+    ///   New keys go last in an ordered dictionary.
+    /// * Two lists are concatenated, `other`'s elements after `self`'s.
+    /// * In any other case `other` wins, including mismatched types, e.g. a
+    ///   `Dict` in `self` is replaced by an `Integer` under the same key in
+    ///   `other` and vice versa.
+    pub fn merge_deep(self, other: BNode) -> BNode {
+        // Pending merges are kept on the heap instead of the call stack, so
+        // deeply nested nodes cannot overflow it.
+        let mut work = vec![MergeWork::Merge(self, other)];
+        let mut merged: Vec<BNode> = vec![];
+
+        while let Some(item) = work.pop() {
+            match item {
+                MergeWork::Merge(mut dict, other) if dict.is_dict() && other.is_dict() => {
+                    let mut shared = vec![];
+                    for (key, value) in other.into_dict_iter().unwrap() {
+                        match dict.get_mut(&key) {
+                            Some(current) => shared.push((key, core::mem::take(current), value)),
+                            None => {
+                                dict.insert_entry(key, value).unwrap();
+                            }
+                        }
+                    }
+
+                    let (keys, pairs): (Vec<_>, Vec<_>) = shared
+                        .into_iter()
+                        .map(|(key, current, value)| (key, MergeWork::Merge(current, value)))
+                        .unzip();
+                    work.push(MergeWork::Assemble(dict, keys));
+                    work.extend(pairs.into_iter().rev());
+                }
+                MergeWork::Merge(BNode::List(mut list), BNode::List(other)) => {
+                    list.extend(other);
+                    merged.push(list.into());
+                }
+                MergeWork::Merge(_, other) => merged.push(other),
+                MergeWork::Assemble(mut dict, keys) => {
+                    let values = merged.split_off(merged.len() - keys.len());
+                    for (key, value) in keys.iter().zip(values) {
+                        *dict.get_mut(key).unwrap() = value;
+                    }
+                    merged.push(dict);
+                }
+            }
+        }
+
+        merged.pop().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_from_str, BDict, BNode};
+
+    #[test]
+    fn test_dict_entries() {
+        let mut bnode = BNode::Dict(BDict::new());
+
+        assert_eq!(Ok(None), bnode.insert_entry("foo".into(), 1.into()));
+        assert_eq!(
+            Ok(Some(BNode::Integer(1))),
+            bnode.insert_entry("foo".into(), 2.into())
+        );
+
+        *bnode.get_entry_mut("foo").unwrap().unwrap() = BNode::from("spam");
+        assert_eq!("d3:foo4:spame", bnode.to_string());
+        assert_eq!(None, bnode.get_entry_mut("bar").unwrap());
+
+        assert_eq!(Ok(Some(BNode::from("spam"))), bnode.remove_entry("foo"));
+        assert_eq!(Ok(None), bnode.remove_entry("foo"));
+        assert!(bnode.is_empty());
+    }
+
+    #[test]
+    fn test_dict_entries_not_a_dictionary() {
+        let nodes = [BNode::Integer(1), BNode::from("spam"), BNode::List(vec![])];
+        for mut node in nodes {
+            let err = Err(format!("expected dict, found {}", node.type_name()));
+            assert_eq!(err, node.insert_entry("foo".into(), 1.into()));
+            assert_eq!(err, node.remove_entry("foo"));
+            assert!(node.get_entry_mut("foo").is_err());
+        }
+    }
+
+    #[test]
+    fn test_dict_get() {
+        let mut bnode = parse_from_str("d3:fooi1ee").unwrap();
+
+        assert!(bnode.contains_key("foo"));
+        assert_eq!(Some(&BNode::Integer(1)), bnode.get("foo"));
+        *bnode.get_mut("foo").unwrap() = 2.into();
+        assert_eq!("d3:fooi2ee", bnode.to_string());
+
+        assert!(!bnode.contains_key("bar"));
+        assert_eq!(None, bnode.get("bar"));
+        assert_eq!(None, bnode.get_mut("bar"));
+
+        let mut list = parse_from_str("l3:fooe").unwrap();
+        assert!(!list.contains_key("foo"));
+        assert_eq!(None, list.get("foo"));
+        assert_eq!(None, list.get_mut("foo"));
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_ordered_dict_entries() {
+        let mut bnode = crate::parse_ordered(&mut "d1:ci3e1:ai1e1:bi2ee".bytes()).unwrap();
+        let keys = |node: &BNode| {
+            node.iter_dict()
+                .unwrap()
+                .map(|(k, _)| k.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(bnode.is_dict());
+        assert!(bnode.contains_key("a"));
+        assert_eq!(
+            Err("expected dict, found ordered dict".to_string()),
+            bnode.as_dict().map(|_| ())
+        );
+        assert!(bnode.entry("a".into()).is_err());
+
+        assert_eq!(Ok(None), bnode.insert_entry("z".into(), 4.into()));
+        assert_eq!(Ok(Some(BNode::Integer(1))), bnode.remove_entry("a"));
+        *bnode.get_entry_mut("b").unwrap().unwrap() = 5.into();
+        assert_eq!(vec!["c", "b", "z"], keys(&bnode));
+
+        bnode.rename_key("b", "y".into()).unwrap();
+        assert_eq!(vec!["c", "y", "z"], keys(&bnode));
+        bnode.rename_key("c", "z".into()).unwrap();
+        assert_eq!(vec!["y", "z"], keys(&bnode));
+        assert_eq!(BNode::Integer(3), bnode["z"]);
+
+        let doubled = bnode
+            .clone()
+            .map_values(|n| (n.as_integer().unwrap() * 2).into())
+            .unwrap();
+        assert_eq!(vec!["y", "z"], keys(&doubled));
+        assert_eq!("d1:yi10e1:zi6ee", doubled.to_string());
+        assert_eq!(vec!["z"], keys(&bnode.clone_and_remove_key("y").unwrap()));
+
+        let other = parse_from_str("d1:ai1e1:yi0ee").unwrap();
+        let merged = bnode.clone().merge_dict(other.clone()).unwrap();
+        assert_eq!(vec!["y", "z", "a"], keys(&merged));
+        assert_eq!("d1:ai1e1:yi0e1:zi3ee", merged.to_string());
+        assert_eq!(merged, other.merge_dict_self_wins(bnode.clone()).unwrap());
+
+        assert_eq!(
+            Ok(parse_from_str("d1:yi5e1:zi3ee")
+                .unwrap()
+                .into_dict()
+                .unwrap()),
+            bnode.into_dict()
+        );
+    }
+
+    #[test]
+    fn test_dict_get_or_default() {
+        let mut bnode = parse_from_str("d3:fooi1ee").unwrap();
+        let default = BNode::Integer(0);
+
+        assert_eq!(&BNode::Integer(1), bnode.get_or_default("foo", &default));
+        assert_eq!(&default, bnode.get_or_default("bar", &default));
+        let list = BNode::List(vec![]);
+        assert_eq!(&default, list.get_or_default("foo", &default));
+
+        *bnode.get_or_insert_default("foo".into(), 0.into()).unwrap() = 2.into();
+        *bnode.get_or_insert_default("bar".into(), 0.into()).unwrap() = 3.into();
+        assert_eq!(
+            Ok(&mut BNode::Integer(4)),
+            bnode.get_or_insert_default("baz".into(), 4.into())
+        );
+        assert_eq!("d3:bari3e3:bazi4e3:fooi2ee", bnode.to_string());
+
+        assert_eq!(
+            Err("expected dict, found list".to_string()),
+            BNode::List(vec![]).get_or_insert_default("foo".into(), 0.into())
+        );
+    }
+
+    #[test]
+    fn test_dict_entry() {
+        let mut bnode = parse_from_str("d5:counti1ee").unwrap();
+
+        for key in ["count", "other"] {
+            bnode
+                .entry(key.into())
+                .unwrap()
+                .and_modify(|v| *v.as_integer_mut().unwrap() += 1)
+                .or_insert(BNode::Integer(0));
+        }
+        assert_eq!("d5:counti2e5:otheri0ee", bnode.to_string());
+
+        let mut list = BNode::List(vec![]);
+        assert_eq!(
+            Err("expected dict, found list".to_string()),
+            list.entry("count".into()).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_map_values() {
+        let bnode = parse_from_str("d1:ai1e1:bd1:ci2eee").unwrap();
+
+        let inc = |n: BNode| match n {
+            BNode::Integer(i) => BNode::Integer(i + 1),
+            n => n,
+        };
+        assert_eq!(
+            "d1:ai2e1:bd1:ci2eee",
+            bnode.clone().map_values(inc).unwrap().to_string()
+        );
+        assert_eq!(
+            "d1:ai2e1:bd1:ci3eee",
+            bnode.map_values_recursive(inc).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_filter_keys() {
+        let bnode = parse_from_str("d8:announce3:udp7:private4:abcd4:infod4:name1:xee").unwrap();
+
+        let shared = bnode.filter_keys(|k| k != "private").unwrap();
+        assert_eq!("d8:announce3:udp4:infod4:name1:xee", shared.to_string());
+        assert_eq!(Ok(BDict::new().into()), bnode.filter_keys(|_| false));
+    }
+
+    #[test]
+    fn test_map_filter_not_a_dictionary() {
+        let list = BNode::List(vec![]);
+        let err = Err("expected dict, found list".to_string());
+
+        assert_eq!(err, list.clone().map_values(|n| n));
+        assert_eq!(err, list.clone().map_values_recursive(|n| n));
+        assert_eq!(err, list.filter_keys(|_| true));
+    }
+
+    #[test]
+    fn test_rename_key() {
+        let mut bnode = parse_from_str("d1:ai1e1:bi2e1:ci3ee").unwrap();
+
+        bnode.rename_key("a", "d".into()).unwrap();
+        assert_eq!("d1:bi2e1:ci3e1:di1ee", bnode.to_string());
+
+        bnode.rename_key("b", "c".into()).unwrap();
+        assert_eq!("d1:ci2e1:di1ee", bnode.to_string());
+
+        assert_eq!(
+            Err("key not found".to_string()),
+            bnode.rename_key("x", "y".into())
+        );
+        assert_eq!(
+            Err("expected dict, found list".to_string()),
+            BNode::List(vec![]).rename_key("a", "b".into())
+        );
+    }
+
+    #[test]
+    fn test_manual_dict_is_canonical() {
+        let mut inner = BDict::new();
+        inner.insert("z".into(), 1.into());
+        inner.insert("a".into(), 2.into());
+        inner.insert("z".into(), 3.into());
+
+        let mut dict = BDict::new();
+        dict.insert("info".into(), inner.into());
+        dict.insert("announce".into(), "udp".into());
+        let bnode = BNode::Dict(dict);
+
+        assert_eq!(
+            1,
+            bnode["info"]
+                .as_dict()
+                .unwrap()
+                .keys()
+                .filter(|k| *k == "z")
+                .count()
+        );
+        assert_eq!("d8:announce3:udp4:infod1:ai2e1:zi3eee", bnode.to_string());
+        assert!(crate::validate_canonical(&bnode.to_bytes()).is_ok());
+
+        let mut normalized = bnode.clone();
+        normalized.normalize();
+        assert_eq!(bnode, normalized);
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_normalize_ordered_dict() {
+        let raw = b"d1:zi1e1:ald1:yi2e1:xi3eeee";
+        let mut bnode = crate::parse_ordered(&mut raw.iter().copied()).unwrap();
+        assert_ne!(crate::parse_from_slice(raw).unwrap(), bnode);
+
+        bnode.normalize();
+        assert_eq!(crate::parse_from_slice(raw).unwrap(), bnode);
+        assert!(matches!(&bnode["a"][0], BNode::Dict(_)));
+    }
+
+    #[test]
+    fn test_clone_and_remove_key() {
+        let info = parse_from_str("d4:name1:x7:privatei1e6:source3:abce").unwrap();
+
+        let public = info.clone_and_remove_key("private").unwrap();
+        assert!(!public.contains_key("private"));
+        assert_eq!("d4:name1:x6:source3:abce", public.to_string());
+        assert!(info.contains_key("private"));
+
+        assert_eq!(
+            Err("key not found".to_string()),
+            info.clone_and_remove_key("missing")
+        );
+        assert_eq!(
+            Err("expected dict, found integer".to_string()),
+            BNode::Integer(1).clone_and_remove_key("private")
+        );
+    }
+
+    #[test]
+    fn test_clone_and_keep_keys() {
+        let info = parse_from_str("d4:name1:x7:privatei1e6:source3:abce").unwrap();
+
+        let kept = info
+            .clone_and_keep_keys(&["name", "source", "missing"])
+            .unwrap();
+        assert_eq!("d4:name1:x6:source3:abce", kept.to_string());
+        assert_eq!(3, info.len());
+        assert!(BNode::List(vec![]).clone_and_keep_keys(&["name"]).is_err());
+    }
+
+    #[test]
+    fn test_merge_dict() {
+        let a = parse_from_str("d3:bari1e3:fooi1ee").unwrap();
+        let b = parse_from_str("d3:bazi2e3:fooi2ee").unwrap();
+
+        let merged = a.clone().merge_dict(b.clone()).unwrap();
+        assert_eq!("d3:bari1e3:bazi2e3:fooi2ee", merged.to_string());
+
+        let merged = a.merge_dict_self_wins(b).unwrap();
+        assert_eq!("d3:bari1e3:bazi2e3:fooi1ee", merged.to_string());
+    }
+
+    #[test]
+    fn test_merge_dict_disjoint() {
+        let a = parse_from_str("d1:ai1ee").unwrap();
+        let b = parse_from_str("d1:bi2ee").unwrap();
+
+        assert_eq!(
+            "d1:ai1e1:bi2ee",
+            a.clone().merge_dict(b.clone()).unwrap().to_string()
+        );
+        assert_eq!(a.clone().merge_dict(b.clone()), a.merge_dict_self_wins(b));
+    }
+
+    #[test]
+    fn test_merge_dict_not_a_dictionary() {
+        let dict = BNode::Dict(BDict::new());
+        let err = |found: &str| Err(format!("expected dict, found {}", found));
+
+        assert_eq!(err("integer"), dict.clone().merge_dict(BNode::Integer(1)));
+        assert_eq!(err("list"), BNode::List(vec![]).merge_dict(dict.clone()));
+        assert_eq!(err("bytes"), dict.merge_dict_self_wins(BNode::from("spam")));
+    }
+
+    #[test]
+    fn test_merge_deep() {
+        let a = parse_from_str("d8:announce1:a4:infod5:filesld6:lengthi1eee4:named4:fulli1eeee")
+            .unwrap();
+        let b = parse_from_str("d7:comment1:c4:infod5:filesld6:lengthi2eee4:named5:shorti2eeee")
+            .unwrap();
+
+        assert_eq!(
+            "d8:announce1:a7:comment1:c4:infod5:filesld6:lengthi1eed6:lengthi2eee4:named4:fulli1e5:shorti2eeee",
+            a.merge_deep(b).to_string()
+        );
+    }
+
+    #[test]
+    fn test_map_values_recursive_deeply_nested() {
+        let depth = 5000;
+        let raw = "d1:a".repeat(depth) + "i1e" + &"e".repeat(depth);
+
+        let mut node = parse_from_str(&raw)
+            .unwrap()
+            .map_values_recursive(|v| BNode::Integer(v.as_integer().unwrap() + 1))
+            .unwrap();
+        for _ in 0..depth {
+            node = node.into_dict().unwrap().remove("a").unwrap();
+        }
+        assert_eq!(BNode::Integer(2), node);
+    }
+
+    #[test]
+    fn test_merge_deep_deeply_nested() {
+        let depth = 5000;
+        let nested = |leaf: &str| {
+            parse_from_str(&("d1:a".repeat(depth) + leaf + &"e".repeat(depth))).unwrap()
+        };
+
+        let mut node = nested("d1:xi1ee").merge_deep(nested("d1:yi2ee"));
+        for _ in 0..depth {
+            node = node.into_dict().unwrap().remove("a").unwrap();
+        }
+        assert_eq!(parse_from_str("d1:xi1e1:yi2ee").unwrap(), node);
+    }
+
+    #[test]
+    fn test_merge_deep_mismatched_types() {
+        let a = parse_from_str("d4:infod4:namei1eee").unwrap();
+        let b = parse_from_str("d4:infoi42ee").unwrap();
+
+        assert_eq!("d4:infoi42ee", a.clone().merge_deep(b.clone()).to_string());
+        assert_eq!(a.clone(), b.merge_deep(a));
+        assert_eq!(
+            BNode::Integer(1),
+            BNode::List(vec![]).merge_deep(BNode::Integer(1))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_merge_deep_ordered_dict() {
+        let a = crate::parse_ordered(&mut "d4:infod4:namei1e1:xi1eee".bytes()).unwrap();
+        let b = parse_from_str("d1:ai2e4:infod1:ai2e4:namei2eee").unwrap();
+        let keys = |node: &BNode| {
+            node.iter_dict()
+                .unwrap()
+                .map(|(k, _)| k.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let merged = a.clone().merge_deep(b);
+        assert_eq!(vec!["info", "a"], keys(&merged));
+        assert_eq!(vec!["name", "x", "a"], keys(&merged["info"]));
+        assert_eq!("d1:ai2e4:infod1:ai2e4:namei2e1:xi1eee", merged.to_string());
+
+        let doubled = a
+            .map_values_recursive(|n| (n.as_integer().unwrap() * 2).into())
+            .unwrap();
+        assert_eq!(vec!["name", "x"], keys(&doubled["info"]));
+        assert_eq!("d4:infod4:namei2e1:xi2eee", doubled.to_string());
+    }
+}
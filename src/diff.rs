@@ -0,0 +1,218 @@
+use crate::prelude::*;
+use crate::BNode;
+use alloc::collections::BTreeMap;
+
+/// Structural difference between two nodes, produced by [`BNode::diff`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BNodeDiff {
+    Same,
+    /// The node was replaced, holds the old and the new node.
+    Changed(BNode, BNode),
+    /// A list element appended at this position.
+    Added(BNode),
+    /// A list element dropped from this position.
+    Removed(BNode),
+    /// Element-wise differences of two lists.
+    ListDiff(Vec<BNodeDiff>),
+    /// Differences of the keys which are not equal in both dictionaries.
+    DictDiff(BTreeMap<String, KeyDiff>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum KeyDiff {
+    Added(BNode),
+    Removed(BNode),
+    Modified(BNodeDiff),
+}
+
+impl BNodeDiff {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            BNodeDiff::Same => true,
+            BNodeDiff::ListDiff(diffs) => diffs.iter().all(BNodeDiff::is_empty),
+            BNodeDiff::DictDiff(diffs) => diffs.values().all(|diff| match diff {
+                KeyDiff::Modified(diff) => diff.is_empty(),
+                _ => false,
+            }),
+            _ => false,
+        }
+    }
+
+    /// Reconstructs the other side of the diff from `base`, which is expected
+    /// to be equal to the node the diff was created from.
+    pub fn apply(&self, base: BNode) -> BNode {
+        match (self, base) {
+            (BNodeDiff::Same, base) | (BNodeDiff::Removed(_), base) => base,
+            (BNodeDiff::Changed(_, new), _) | (BNodeDiff::Added(new), _) => new.clone(),
+            (BNodeDiff::ListDiff(diffs), BNode::List(list)) => {
+                let mut base = list.into_iter();
+                let mut list = vec![];
+
+                for diff in diffs {
+                    match diff {
+                        BNodeDiff::Added(node) => list.push(node.clone()),
+                        BNodeDiff::Removed(_) => {
+                            base.next();
+                        }
+                        diff => {
+                            if let Some(node) = base.next() {
+                                list.push(diff.apply(node));
+                            }
+                        }
+                    }
+                }
+                list.extend(base);
+
+                list.into()
+            }
+            (BNodeDiff::DictDiff(diffs), mut dict) if dict.is_dict() => {
+                for (key, diff) in diffs {
+                    match diff {
+                        KeyDiff::Added(node) => {
+                            dict.insert_entry(key.clone(), node.clone()).unwrap();
+                        }
+                        KeyDiff::Removed(_) => {
+                            dict.remove_entry(key).unwrap();
+                        }
+                        KeyDiff::Modified(diff) => {
+                            if let Some(node) = dict.get_mut(key) {
+                                *node = diff.apply(core::mem::take(node));
+                            }
+                        }
+                    }
+                }
+
+                dict
+            }
+            (_, base) => base,
+        }
+    }
+}
+
+impl BNode {
+    /// Describes how to turn `self` into `other`.
+    pub fn diff(&self, other: &BNode) -> BNodeDiff {
+        if self == other {
+            return BNodeDiff::Same;
+        }
+
+        match (self, other) {
+            (BNode::List(old), BNode::List(new)) => {
+                let mut diffs: Vec<BNodeDiff> = old
+                    .iter()
+                    .zip(new)
+                    .map(|(old, new)| old.diff(new))
+                    .collect();
+                diffs.extend(new.iter().skip(old.len()).cloned().map(BNodeDiff::Added));
+                diffs.extend(old.iter().skip(new.len()).cloned().map(BNodeDiff::Removed));
+
+                BNodeDiff::ListDiff(diffs)
+            }
+            (old, new) if old.is_dict() && new.is_dict() => {
+                let mut diffs = BTreeMap::new();
+
+                for (key, value) in old.iter_dict().unwrap() {
+                    match new.get(key) {
+                        Some(other) if other == value => {}
+                        Some(other) => {
+                            diffs.insert(key.to_string(), KeyDiff::Modified(value.diff(other)));
+                        }
+                        None => {
+                            diffs.insert(key.to_string(), KeyDiff::Removed(value.clone()));
+                        }
+                    }
+                }
+                for (key, value) in new.iter_dict().unwrap() {
+                    if !old.contains_key(key) {
+                        diffs.insert(key.to_string(), KeyDiff::Added(value.clone()));
+                    }
+                }
+
+                BNodeDiff::DictDiff(diffs)
+            }
+            _ => BNodeDiff::Changed(self.clone(), other.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BNodeDiff, KeyDiff};
+    use crate::{parse_from_str, BNode};
+
+    #[test]
+    fn test_diff_same() {
+        let bnode = parse_from_str("d3:fooli1eee").unwrap();
+
+        let diff = bnode.diff(&bnode);
+        assert_eq!(BNodeDiff::Same, diff);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_diff_changed_integer() {
+        let diff = BNode::Integer(1).diff(&BNode::Integer(2));
+
+        assert_eq!(
+            BNodeDiff::Changed(BNode::Integer(1), BNode::Integer(2)),
+            diff
+        );
+        assert!(!diff.is_empty());
+        assert_eq!(BNode::Integer(2), diff.apply(BNode::Integer(1)));
+    }
+
+    #[test]
+    fn test_diff_dict() {
+        let old = parse_from_str("d1:ai1e1:bi2e1:cli1eee").unwrap();
+        let new = parse_from_str("d1:ai1e1:cli1ei2ee1:di4ee").unwrap();
+
+        let diff = old.diff(&new);
+        match &diff {
+            BNodeDiff::DictDiff(diffs) => {
+                assert_eq!(3, diffs.len());
+                assert_eq!(Some(&KeyDiff::Removed(BNode::Integer(2))), diffs.get("b"));
+                assert_eq!(Some(&KeyDiff::Added(BNode::Integer(4))), diffs.get("d"));
+                assert_eq!(
+                    Some(&KeyDiff::Modified(BNodeDiff::ListDiff(vec![
+                        BNodeDiff::Same,
+                        BNodeDiff::Added(BNode::Integer(2))
+                    ]))),
+                    diffs.get("c")
+                );
+            }
+            _ => panic!("should be a dictionary diff"),
+        }
+
+        assert_eq!(new, diff.apply(old));
+    }
+
+    #[test]
+    fn test_diff_apply_list() {
+        let old = parse_from_str("li1ei2ei3ee").unwrap();
+        let new = parse_from_str("li1ei5ee").unwrap();
+
+        assert_eq!(new, old.diff(&new).apply(old.clone()));
+        assert_eq!(old, new.diff(&old).apply(new.clone()));
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_diff_ordered_dict() {
+        let old = crate::parse_ordered(&mut "d1:ci1e1:ai1e1:bi2ee".bytes()).unwrap();
+        let new = parse_from_str("d1:ai1e1:bi3e1:di4ee").unwrap();
+
+        let diff = old.diff(&new);
+        match &diff {
+            BNodeDiff::DictDiff(diffs) => {
+                assert_eq!(3, diffs.len());
+                assert_eq!(Some(&KeyDiff::Removed(BNode::Integer(1))), diffs.get("c"));
+            }
+            _ => panic!("should be a dictionary diff"),
+        }
+
+        let applied = diff.apply(old);
+        let keys: Vec<_> = applied.iter_dict().unwrap().map(|(k, _)| k).collect();
+        assert_eq!(vec!["a", "b", "d"], keys);
+        assert_eq!(new.to_string(), applied.to_string());
+    }
+}
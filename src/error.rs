@@ -0,0 +1,143 @@
+use crate::prelude::*;
+use crate::Token;
+use core::fmt::Display;
+
+/// What went wrong, see [`Error::kind`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
+    /// The input ended in the middle of a value.
+    UnexpectedEof,
+    /// A byte which cannot appear at this point of the input.
+    InvalidToken(u8),
+    LeadingZero,
+    NegativeZero,
+    EmptyInteger,
+    DictKeyNotUtf8,
+    DictKeyOutOfOrder,
+    DuplicateDictKey,
+    IntegerOverflow,
+    UnexpectedToken {
+        expected: &'static str,
+        found: Token,
+    },
+    /// A [`ParserConfig`](crate::ParserConfig) limit was exceeded.
+    LimitExceeded {
+        limit: &'static str,
+        max: usize,
+    },
+    /// Reading the input failed, holding the message of the I/O error.
+    Io(String),
+    /// Any other error, e.g. raised through serde.
+    Custom(String),
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            ErrorKind::InvalidToken(byte) if byte.is_ascii_graphic() => {
+                write!(f, "invalid byte `{}`", *byte as char)
+            }
+            ErrorKind::InvalidToken(byte) => write!(f, "invalid byte 0x{:02x}", byte),
+            ErrorKind::LeadingZero => write!(f, "Leading zero is not permitted"),
+            ErrorKind::NegativeZero => write!(f, "Negative zero is not permitted"),
+            ErrorKind::EmptyInteger => write!(f, "Integer cannot be empty"),
+            ErrorKind::DictKeyNotUtf8 => write!(f, "dictionary key is not valid UTF-8"),
+            ErrorKind::DictKeyOutOfOrder => write!(f, "dictionary keys are not sorted"),
+            ErrorKind::DuplicateDictKey => write!(f, "duplicate dictionary key"),
+            ErrorKind::IntegerOverflow => write!(f, "integer does not fit in i64"),
+            ErrorKind::UnexpectedToken { expected, found } => {
+                write!(f, "expected {}, found {:?}", expected, found)
+            }
+            ErrorKind::LimitExceeded { limit, max } => {
+                write!(f, "{} exceeds the limit of {}", limit, max)
+            }
+            ErrorKind::Io(msg) => write!(f, "io error: {}", msg),
+            ErrorKind::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Error {
+    /// The zero-based offset of the byte consumed last when the error was
+    /// raised, usually the offending byte. Once the input is exhausted it's the
+    /// offset one past its last byte. [`Error::NO_POSITION`] if the error isn't
+    /// tied to the input.
+    pub position: u64,
+    kind: ErrorKind,
+}
+
+impl Error {
+    /// Sentinel [`Error::position`] of errors raised outside of parsing, e.g.
+    /// through serde.
+    pub const NO_POSITION: u64 = u64::MAX;
+
+    pub fn new(kind: ErrorKind, position: u64) -> Error {
+        Error { position, kind }
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.position {
+            Error::NO_POSITION => write!(f, "bencode error: {}", self.kind),
+            position => write!(f, "bencode error at position {}: {}", position, self.kind),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(feature = "std")]
+/// Either a parse error or a failed write, for callers both parsing and
+/// encoding through [`IoResult`](crate::IoResult).
+#[derive(Debug)]
+pub enum BencodeError {
+    Parse(Error),
+    Io(std::io::Error),
+}
+
+#[cfg(feature = "std")]
+impl Display for BencodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // The cause is left to `source`, so error reporters don't print it
+        // twice.
+        match self {
+            BencodeError::Parse(_) => f.write_str("bencode parse error"),
+            BencodeError::Io(_) => f.write_str("io error"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for BencodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            BencodeError::Parse(e) => Some(e),
+            BencodeError::Io(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for BencodeError {
+    fn from(e: Error) -> Self {
+        BencodeError::Parse(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for BencodeError {
+    fn from(e: std::io::Error) -> Self {
+        BencodeError::Io(e)
+    }
+}
@@ -0,0 +1,241 @@
+use crate::prelude::*;
+use crate::reader::{Copied, Item, Reader};
+use crate::{unexpected, Lexer, ParserConfig, Result, Token};
+
+/// A parsing step reported by [`BNodeEvents`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Event {
+    StartList,
+    EndList,
+    StartDict,
+    EndDict,
+    Key(String),
+    Integer(i64),
+    #[cfg(feature = "bigint")]
+    BigInteger(Box<num_bigint::BigInt>),
+    Bytes(Vec<u8>),
+}
+
+/// Reports the structure of the input as a sequence of [`Event`]s instead of
+/// building a tree, so arbitrarily large input can be processed in constant
+/// memory apart from the byte strings themselves.
+///
+/// The iterator stops after the first error.
+pub struct BNodeEvents<'a, T>
+where
+    T: Iterator<Item = u8>,
+{
+    lexer: Lexer<'a, T>,
+    reader: Reader,
+    started: bool,
+    done: bool,
+}
+
+impl<'a, T> BNodeEvents<'a, T>
+where
+    T: Iterator<Item = u8>,
+{
+    pub fn new(stream: &'a mut T) -> BNodeEvents<'a, T> {
+        BNodeEvents::with_config(stream, ParserConfig::default())
+    }
+
+    /// Enforces the limits and key checks of `config` like
+    /// [`parse_with_config`](crate::parse_with_config). Checking the keys
+    /// keeps those of every open dictionary in memory.
+    pub fn with_config(stream: &'a mut T, config: ParserConfig) -> BNodeEvents<'a, T> {
+        BNodeEvents {
+            lexer: Lexer::new(stream),
+            reader: Reader::new(config),
+            started: false,
+            done: false,
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        if self.started && self.reader.depth() == 0 {
+            self.done = true;
+            return match self.lexer.next_token()? {
+                Token::EOF => Ok(None),
+                found => throw!(unexpected("EOF", found), self.lexer.position()),
+            };
+        }
+        self.started = true;
+
+        Ok(Some(
+            match self.reader.next_item(&mut self.lexer, &mut Copied)? {
+                Item::Integer(value) => Event::Integer(value),
+                #[cfg(feature = "bigint")]
+                Item::BigInteger(value) => Event::BigInteger(value),
+                Item::Bytes(bytes) => Event::Bytes(bytes),
+                Item::Key(key) => Event::Key(key),
+                Item::StartList => Event::StartList,
+                Item::EndList => Event::EndList,
+                Item::StartDict => Event::StartDict,
+                Item::EndDict => Event::EndDict,
+            },
+        ))
+    }
+}
+
+impl<T> Iterator for BNodeEvents<'_, T>
+where
+    T: Iterator<Item = u8>,
+{
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_event() {
+            Ok(event) => event.map(Ok),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BNodeEvents, Event};
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{
+        parse_from_slice, parse_with_config, BDict, BNode, ErrorKind, ParserConfig, Result,
+    };
+
+    /// Builds the tree from the events like a consumer of the API would.
+    fn rebuild(input: &[u8]) -> Result<BNode> {
+        let mut stream = input.iter().copied();
+        let mut stack: Vec<(BNode, Option<String>)> = vec![];
+        let mut root = None;
+
+        for event in BNodeEvents::new(&mut stream) {
+            let node = match event? {
+                Event::StartList => {
+                    stack.push((BNode::List(vec![]), None));
+                    continue;
+                }
+                Event::StartDict => {
+                    stack.push((BNode::Dict(BDict::new()), None));
+                    continue;
+                }
+                Event::Key(key) => {
+                    stack.last_mut().unwrap().1 = Some(key);
+                    continue;
+                }
+                Event::EndList | Event::EndDict => stack.pop().unwrap().0,
+                Event::Integer(i) => BNode::Integer(i),
+                #[cfg(feature = "bigint")]
+                Event::BigInteger(i) => BNode::BigInteger(i),
+                Event::Bytes(bytes) => BNode::Bytes(bytes),
+            };
+
+            match stack.last_mut() {
+                Some((BNode::List(list), _)) => list.push(node),
+                Some((BNode::Dict(dict), key)) => {
+                    dict.insert(key.take().unwrap(), node);
+                }
+                _ => root = Some(node),
+            }
+        }
+
+        Ok(root.unwrap())
+    }
+
+    #[test]
+    fn test_events() {
+        let mut stream = b"d1:ali1e4:spame1:bdee".iter().copied();
+        let events: Vec<_> = BNodeEvents::new(&mut stream)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                Event::StartDict,
+                Event::Key("a".to_string()),
+                Event::StartList,
+                Event::Integer(1),
+                Event::Bytes(b"spam".to_vec()),
+                Event::EndList,
+                Event::Key("b".to_string()),
+                Event::StartDict,
+                Event::EndDict,
+                Event::EndDict,
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn test_events_rebuild() {
+        let cases: [&[u8]; 6] = [
+            b"i-42e",
+            b"0:",
+            b"le",
+            b"lli1eeli2ei3eee",
+            b"d1:ad1:bd1:cleee1:d4:spame",
+            DEBIAN_TORRENT.as_bytes(),
+        ];
+        for case in cases {
+            assert_eq!(parse_from_slice(case).unwrap(), rebuild(case).unwrap());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_events_big_integer() {
+        let raw = b"li99999999999999999999ee";
+        assert_eq!(parse_from_slice(raw).unwrap(), rebuild(raw).unwrap());
+    }
+
+    #[test]
+    fn test_events_with_config() {
+        let config = ParserConfig {
+            strict_dict_keys: true,
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let cases: [&[u8]; 3] = [b"d1:bi1e1:ai2ee", b"d1:ai1e1:ai2ee", b"llleee"];
+        for case in cases {
+            let mut stream = case.iter().copied();
+            let err = BNodeEvents::with_config(&mut stream, config.clone())
+                .find_map(|event| event.err())
+                .unwrap();
+
+            let expected = parse_with_config(&mut case.iter().copied(), &config).unwrap_err();
+            assert_eq!(expected.kind(), err.kind());
+            assert_eq!(expected.position, err.position);
+        }
+    }
+
+    #[test]
+    fn test_events_failed() {
+        let cases: [(&[u8], ErrorKind); 5] = [
+            (b"", ErrorKind::UnexpectedEof),
+            (b"l4:halo", ErrorKind::UnexpectedEof),
+            (b"d1:\xffi1ee", ErrorKind::DictKeyNotUtf8),
+            (
+                b"i1ei2e",
+                ErrorKind::UnexpectedToken {
+                    expected: "EOF",
+                    found: crate::Token::IntegerBegin,
+                },
+            ),
+            (b"ie", ErrorKind::EmptyInteger),
+        ];
+        for (case, kind) in cases {
+            let mut stream = case.iter().copied();
+            let mut events = BNodeEvents::new(&mut stream);
+            let err = events.find_map(|event| event.err()).unwrap();
+
+            assert_eq!(&kind, err.kind());
+            assert!(events.next().is_none());
+            let expected = parse_from_slice(case).unwrap_err();
+            assert_eq!(expected.kind(), err.kind());
+            assert_eq!(expected.position, err.position);
+        }
+    }
+}
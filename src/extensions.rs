@@ -1,4 +1,5 @@
-use crate::BNode;
+use crate::prelude::*;
+use crate::{BDict, BList, BNode, BNodeType, Error, ErrorKind};
 
 impl From<i64> for BNode {
     fn from(value: i64) -> Self {
@@ -6,6 +7,76 @@ impl From<i64> for BNode {
     }
 }
 
+/// Defaults to `BNode::Integer(0)`, the zero value of the only scalar which
+/// isn't a container or a byte string. `BList` and `BDict` are aliases of `Vec`
+/// and `BTreeMap`, so they already default to being empty.
+impl Default for BNode {
+    fn default() -> Self {
+        BNode::Integer(0)
+    }
+}
+
+impl From<i8> for BNode {
+    fn from(value: i8) -> Self {
+        BNode::Integer(value.into())
+    }
+}
+
+impl From<i16> for BNode {
+    fn from(value: i16) -> Self {
+        BNode::Integer(value.into())
+    }
+}
+
+impl From<i32> for BNode {
+    fn from(value: i32) -> Self {
+        BNode::Integer(value.into())
+    }
+}
+
+impl From<u8> for BNode {
+    fn from(value: u8) -> Self {
+        BNode::Integer(value.into())
+    }
+}
+
+impl From<u16> for BNode {
+    fn from(value: u16) -> Self {
+        BNode::Integer(value.into())
+    }
+}
+
+impl From<u32> for BNode {
+    fn from(value: u32) -> Self {
+        BNode::Integer(value.into())
+    }
+}
+
+impl From<bool> for BNode {
+    fn from(value: bool) -> Self {
+        BNode::Integer(value.into())
+    }
+}
+
+impl TryFrom<u64> for BNode {
+    type Error = Error;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match i64::try_from(value) {
+            Ok(value) => Ok(BNode::Integer(value)),
+            Err(_) => Err(Error::new(ErrorKind::IntegerOverflow, Error::NO_POSITION)),
+        }
+    }
+}
+
+impl TryFrom<usize> for BNode {
+    type Error = Error;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        BNode::try_from(value as u64)
+    }
+}
+
 impl From<String> for BNode {
     fn from(value: String) -> Self {
         BNode::Bytes(value.into())
@@ -30,14 +101,519 @@ impl From<&[u8]> for BNode {
     }
 }
 
-impl From<Vec<BNode>> for BNode {
-    fn from(value: Vec<BNode>) -> Self {
+impl From<BList> for BNode {
+    fn from(value: BList) -> Self {
         BNode::List(value)
     }
 }
 
-impl From<std::collections::BTreeMap<String, BNode>> for BNode {
-    fn from(value: std::collections::BTreeMap<String, BNode>) -> Self {
+impl From<BDict> for BNode {
+    fn from(value: BDict) -> Self {
         BNode::Dict(value)
     }
 }
+
+impl TryFrom<BNode> for i64 {
+    type Error = String;
+
+    fn try_from(value: BNode) -> Result<Self, Self::Error> {
+        i64::try_from(&value)
+    }
+}
+
+impl TryFrom<BNode> for Vec<u8> {
+    type Error = String;
+
+    fn try_from(value: BNode) -> Result<Self, Self::Error> {
+        value
+            .into_bytes()
+            .map_err(|node| node.type_mismatch(BNodeType::Bytes))
+    }
+}
+
+impl TryFrom<BNode> for String {
+    type Error = String;
+
+    fn try_from(value: BNode) -> Result<Self, Self::Error> {
+        let bytes = Vec::<u8>::try_from(value)?;
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+}
+
+impl TryFrom<BNode> for BList {
+    type Error = String;
+
+    fn try_from(value: BNode) -> Result<Self, Self::Error> {
+        value
+            .into_list()
+            .map_err(|node| node.type_mismatch(BNodeType::List))
+    }
+}
+
+impl TryFrom<BNode> for BDict {
+    type Error = String;
+
+    fn try_from(value: BNode) -> Result<Self, Self::Error> {
+        value
+            .into_dict()
+            .map_err(|node| node.type_mismatch(BNodeType::Dict))
+    }
+}
+
+impl TryFrom<&BNode> for i64 {
+    type Error = String;
+
+    fn try_from(value: &BNode) -> Result<Self, Self::Error> {
+        match value {
+            BNode::Integer(i) => Ok(*i),
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(_) => Err("integer out of the i64 range".into()),
+            _ => Err(value.type_mismatch(BNodeType::Integer)),
+        }
+    }
+}
+
+impl TryFrom<&BNode> for Vec<u8> {
+    type Error = String;
+
+    fn try_from(value: &BNode) -> Result<Self, Self::Error> {
+        match value {
+            BNode::Bytes(bytes) => Ok(bytes.clone()),
+            _ => Err(value.type_mismatch(BNodeType::Bytes)),
+        }
+    }
+}
+
+impl TryFrom<&BNode> for String {
+    type Error = String;
+
+    fn try_from(value: &BNode) -> Result<Self, Self::Error> {
+        match value {
+            BNode::Bytes(bytes) => core::str::from_utf8(bytes)
+                .map(String::from)
+                .map_err(|e| e.to_string()),
+            _ => Err(value.type_mismatch(BNodeType::Bytes)),
+        }
+    }
+}
+
+impl TryFrom<&BNode> for BList {
+    type Error = String;
+
+    fn try_from(value: &BNode) -> Result<Self, Self::Error> {
+        match value {
+            BNode::List(list) => Ok(list.clone()),
+            _ => Err(value.type_mismatch(BNodeType::List)),
+        }
+    }
+}
+
+impl TryFrom<&BNode> for BDict {
+    type Error = String;
+
+    fn try_from(value: &BNode) -> Result<Self, Self::Error> {
+        match value {
+            BNode::Dict(dict) => Ok(dict.clone()),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => {
+                Ok(dict.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            }
+            _ => Err(value.type_mismatch(BNodeType::Dict)),
+        }
+    }
+}
+
+impl core::ops::Index<usize> for BNode {
+    type Output = BNode;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self {
+            BNode::List(list) => match list.get(index) {
+                Some(node) => node,
+                None => panic!(
+                    "index out of bounds: the len is {} but the index is {}",
+                    list.len(),
+                    index
+                ),
+            },
+            _ => panic!("cannot index {} with index {}", self.type_name(), index),
+        }
+    }
+}
+
+impl core::ops::IndexMut<usize> for BNode {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let found = self.type_name();
+        match self {
+            BNode::List(list) => {
+                let len = list.len();
+                match list.get_mut(index) {
+                    Some(node) => node,
+                    None => panic!(
+                        "index out of bounds: the len is {} but the index is {}",
+                        len, index
+                    ),
+                }
+            }
+            _ => panic!("cannot index {} with index {}", found, index),
+        }
+    }
+}
+
+impl core::ops::Index<&str> for BNode {
+    type Output = BNode;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        match self {
+            BNode::Dict(dict) => match dict.get(key) {
+                Some(node) => node,
+                None => panic!("key `{}` not found in dict", key),
+            },
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => match dict.get(key) {
+                Some(node) => node,
+                None => panic!("key `{}` not found in dict", key),
+            },
+            _ => panic!("cannot index {} with key `{}`", self.type_name(), key),
+        }
+    }
+}
+
+impl core::ops::IndexMut<&str> for BNode {
+    fn index_mut(&mut self, key: &str) -> &mut Self::Output {
+        let found = self.type_name();
+        match self {
+            BNode::Dict(dict) => match dict.get_mut(key) {
+                Some(node) => node,
+                None => panic!("key `{}` not found in dict", key),
+            },
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => match dict.get_mut(key) {
+                Some(node) => node,
+                None => panic!("key `{}` not found in dict", key),
+            },
+            _ => panic!("cannot index {} with key `{}`", found, key),
+        }
+    }
+}
+
+impl PartialEq<[u8]> for BNode {
+    fn eq(&self, other: &[u8]) -> bool {
+        matches!(self, BNode::Bytes(bytes) if bytes.as_slice() == other)
+    }
+}
+
+impl PartialEq<str> for BNode {
+    fn eq(&self, other: &str) -> bool {
+        *self == *other.as_bytes()
+    }
+}
+
+impl PartialEq<i64> for BNode {
+    fn eq(&self, other: &i64) -> bool {
+        matches!(self, BNode::Integer(i) if i == other)
+    }
+}
+
+impl PartialEq<&[u8]> for BNode {
+    fn eq(&self, other: &&[u8]) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<&str> for BNode {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<BNode> for [u8] {
+    fn eq(&self, other: &BNode) -> bool {
+        *other == *self
+    }
+}
+
+impl PartialEq<BNode> for str {
+    fn eq(&self, other: &BNode) -> bool {
+        *other == *self
+    }
+}
+
+impl PartialEq<BNode> for i64 {
+    fn eq(&self, other: &BNode) -> bool {
+        *other == *self
+    }
+}
+
+impl PartialEq<BNode> for &[u8] {
+    fn eq(&self, other: &BNode) -> bool {
+        *other == **self
+    }
+}
+
+impl PartialEq<BNode> for &str {
+    fn eq(&self, other: &BNode) -> bool {
+        *other == **self
+    }
+}
+
+impl core::hash::Hash for BNode {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        match self {
+            BNode::Integer(i) => {
+                state.write_u8(0);
+                i.hash(state);
+            }
+            BNode::Bytes(bytes) => {
+                state.write_u8(1);
+                bytes.as_slice().hash(state);
+            }
+            BNode::List(list) => {
+                state.write_u8(2);
+                state.write_usize(list.len());
+                for node in list {
+                    node.hash(state);
+                }
+            }
+            BNode::Dict(dict) => {
+                state.write_u8(3);
+                state.write_usize(dict.len());
+                // `BTreeMap` iterates in key order, so equal dictionaries
+                // always feed the same sequence.
+                for (key, value) in dict {
+                    key.hash(state);
+                    value.hash(state);
+                }
+            }
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(i) => {
+                state.write_u8(4);
+                i.hash(state);
+            }
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => {
+                state.write_u8(5);
+                state.write_usize(dict.len());
+                // Equality ignores the order of the entries, so must hashing.
+                for (key, value) in crate::sorted_entries(dict) {
+                    key.hash(state);
+                    value.hash(state);
+                }
+            }
+        }
+    }
+}
+
+impl PartialOrd for BNode {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Variants are ordered as `Integer < Bytes < List < Dict`, values of the
+/// same variant are compared naturally or lexicographically. Big integers are
+/// ordered among integers by value, before an `Integer` of the same value.
+/// Ordered dictionaries are compared as dictionaries by their sorted entries,
+/// after a `Dict` with the same entries.
+impl Ord for BNode {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        fn rank(node: &BNode) -> u8 {
+            match node {
+                BNode::Integer(_) => 0,
+                #[cfg(feature = "bigint")]
+                BNode::BigInteger(_) => 0,
+                BNode::Bytes(_) => 1,
+                BNode::List(_) => 2,
+                BNode::Dict(_) => 3,
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(_) => 3,
+            }
+        }
+
+        match (self, other) {
+            (BNode::Integer(a), BNode::Integer(b)) => a.cmp(b),
+            #[cfg(feature = "bigint")]
+            (BNode::BigInteger(a), BNode::BigInteger(b)) => a.cmp(b),
+            #[cfg(feature = "bigint")]
+            (BNode::BigInteger(a), BNode::Integer(b)) => (**a)
+                .cmp(&num_bigint::BigInt::from(*b))
+                .then(core::cmp::Ordering::Less),
+            #[cfg(feature = "bigint")]
+            (BNode::Integer(a), BNode::BigInteger(b)) => num_bigint::BigInt::from(*a)
+                .cmp(b)
+                .then(core::cmp::Ordering::Greater),
+            (BNode::Bytes(a), BNode::Bytes(b)) => a.cmp(b),
+            (BNode::List(a), BNode::List(b)) => a.cmp(b),
+            (BNode::Dict(a), BNode::Dict(b)) => a.iter().cmp(b.iter()),
+            #[cfg(feature = "indexmap")]
+            (BNode::OrderedDict(a), BNode::OrderedDict(b)) => {
+                crate::sorted_entries(a).cmp(&crate::sorted_entries(b))
+            }
+            #[cfg(feature = "indexmap")]
+            (BNode::Dict(a), BNode::OrderedDict(b)) => a
+                .iter()
+                .cmp(crate::sorted_entries(b))
+                .then(core::cmp::Ordering::Less),
+            #[cfg(feature = "indexmap")]
+            (BNode::OrderedDict(a), BNode::Dict(b)) => crate::sorted_entries(a)
+                .into_iter()
+                .cmp(b.iter())
+                .then(core::cmp::Ordering::Greater),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl FromIterator<BNode> for BNode {
+    fn from_iter<I: IntoIterator<Item = BNode>>(iter: I) -> Self {
+        BNode::List(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<(String, BNode)> for BNode {
+    fn from_iter<I: IntoIterator<Item = (String, BNode)>>(iter: I) -> Self {
+        BNode::Dict(iter.into_iter().collect())
+    }
+}
+
+/// Appends to a list, panics if `self` is not a list.
+impl Extend<BNode> for BNode {
+    fn extend<I: IntoIterator<Item = BNode>>(&mut self, iter: I) {
+        match self {
+            BNode::List(list) => list.extend(iter),
+            _ => panic!("not a list"),
+        }
+    }
+}
+
+/// Inserts into a dictionary, panics if `self` is not a dictionary.
+impl Extend<(String, BNode)> for BNode {
+    fn extend<I: IntoIterator<Item = (String, BNode)>>(&mut self, iter: I) {
+        match self {
+            BNode::Dict(dict) => dict.extend(iter),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => dict.extend(iter),
+            _ => panic!("not a dictionary"),
+        }
+    }
+}
+
+fn integer(node: &BNode, op: &str) -> i64 {
+    match node {
+        BNode::Integer(i) => *i,
+        #[cfg(feature = "bigint")]
+        BNode::BigInteger(_) => panic!("cannot {} big integer", op),
+        _ => panic!("cannot {} {}", op, node.type_name()),
+    }
+}
+
+/// Applies `op` to two integers, panicking on any other kind of node and on
+/// overflow, in release builds too.
+fn arithmetic(lhs: &BNode, rhs: &BNode, name: &str, op: fn(i64, i64) -> Option<i64>) -> BNode {
+    integer(lhs, name);
+    integer(rhs, name);
+    lhs.checked(rhs, op)
+        .unwrap_or_else(|| panic!("integer overflow in {}", name))
+}
+
+/// Sums integers, panics on any other kind of node and on overflow.
+impl core::iter::Sum<BNode> for BNode {
+    fn sum<I: Iterator<Item = BNode>>(iter: I) -> Self {
+        iter.fold(BNode::Integer(0), |acc, node| {
+            arithmetic(&acc, &node, "sum", i64::checked_add)
+        })
+    }
+}
+
+impl<'a> core::iter::Sum<&'a BNode> for BNode {
+    fn sum<I: Iterator<Item = &'a BNode>>(iter: I) -> Self {
+        iter.fold(BNode::Integer(0), |acc, node| {
+            arithmetic(&acc, node, "sum", i64::checked_add)
+        })
+    }
+}
+
+/// Multiplies integers, panics on any other kind of node and on overflow.
+impl core::iter::Product<BNode> for BNode {
+    fn product<I: Iterator<Item = BNode>>(iter: I) -> Self {
+        iter.fold(BNode::Integer(1), |acc, node| {
+            arithmetic(&acc, &node, "multiply", i64::checked_mul)
+        })
+    }
+}
+
+impl<'a> core::iter::Product<&'a BNode> for BNode {
+    fn product<I: Iterator<Item = &'a BNode>>(iter: I) -> Self {
+        iter.fold(BNode::Integer(1), |acc, node| {
+            arithmetic(&acc, node, "multiply", i64::checked_mul)
+        })
+    }
+}
+
+impl core::ops::Add for BNode {
+    type Output = BNode;
+
+    /// Panics unless both operands are integers, and on overflow.
+    fn add(self, rhs: BNode) -> BNode {
+        arithmetic(&self, &rhs, "add", i64::checked_add)
+    }
+}
+
+impl core::ops::Sub for BNode {
+    type Output = BNode;
+
+    /// Panics unless both operands are integers, and on overflow.
+    fn sub(self, rhs: BNode) -> BNode {
+        arithmetic(&self, &rhs, "subtract", i64::checked_sub)
+    }
+}
+
+impl core::ops::Mul for BNode {
+    type Output = BNode;
+
+    /// Panics unless both operands are integers, and on overflow.
+    fn mul(self, rhs: BNode) -> BNode {
+        arithmetic(&self, &rhs, "multiply", i64::checked_mul)
+    }
+}
+
+impl core::ops::Neg for BNode {
+    type Output = BNode;
+
+    /// Panics unless the operand is an integer, and on `-i64::MIN`.
+    fn neg(self) -> BNode {
+        arithmetic(&BNode::Integer(0), &self, "negate", i64::checked_sub)
+    }
+}
+
+impl BNode {
+    fn checked(&self, rhs: &BNode, op: fn(i64, i64) -> Option<i64>) -> Option<BNode> {
+        match (self, rhs) {
+            (BNode::Integer(a), BNode::Integer(b)) => op(*a, *b).map(BNode::Integer),
+            _ => None,
+        }
+    }
+
+    /// `None` on overflow or if either operand is not an integer.
+    pub fn checked_add(&self, rhs: &BNode) -> Option<BNode> {
+        self.checked(rhs, i64::checked_add)
+    }
+
+    /// `None` on overflow or if either operand is not an integer.
+    pub fn checked_sub(&self, rhs: &BNode) -> Option<BNode> {
+        self.checked(rhs, i64::checked_sub)
+    }
+
+    /// `None` on overflow or if either operand is not an integer.
+    pub fn checked_mul(&self, rhs: &BNode) -> Option<BNode> {
+        self.checked(rhs, i64::checked_mul)
+    }
+}
+
+impl core::str::FromStr for BNode {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::parse_from_str(s)
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{parse_from_slice, BNode, Error, ErrorKind, Result};
+use std::io::{Read, Write};
+
+const PREFIX_LEN: u64 = 4;
+
+impl BNode {
+    /// Writes the encoding prefixed with its length as a 4-byte big-endian
+    /// integer, as messages of the peer wire protocol are framed. Returns the
+    /// number of bytes written, prefix included.
+    pub fn encode_length_delimited<W: Write>(&self, writer: &mut W) -> std::io::Result<usize> {
+        let payload = self.to_bytes();
+        let len = u32::try_from(payload.len()).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "encoding is longer than u32::MAX bytes",
+            )
+        })?;
+
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(&payload)?;
+
+        Ok(PREFIX_LEN as usize + payload.len())
+    }
+
+    /// Reads a message written by [`BNode::encode_length_delimited`].
+    ///
+    /// A failed read, including a stream ending before the announced length,
+    /// is reported as [`ErrorKind::Io`] without position. Parse errors are
+    /// positioned relative to the start of the frame.
+    pub fn decode_length_delimited<R: Read>(reader: &mut R) -> Result<BNode> {
+        let io_error =
+            |e: std::io::Error| Error::new(ErrorKind::Io(e.to_string()), Error::NO_POSITION);
+
+        let mut prefix = [0; PREFIX_LEN as usize];
+        reader.read_exact(&mut prefix).map_err(io_error)?;
+        let len = u32::from_be_bytes(prefix) as u64;
+
+        // Reading through `take` doesn't trust the prefix to allocate upfront.
+        let mut payload = vec![];
+        reader
+            .take(len)
+            .read_to_end(&mut payload)
+            .map_err(io_error)?;
+        if (payload.len() as u64) < len {
+            return Err(io_error(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+
+        parse_from_slice(&payload).map_err(|mut e| {
+            e.position += PREFIX_LEN;
+            e
+        })
+    }
+
+    /// Parses a byte string holding an encoded value, as some extension
+    /// messages embed. Parse errors keep their kind, positioned relative to
+    /// the start of the byte string.
+    pub fn decode_nested_bencode(&self) -> Result<BNode> {
+        match self {
+            BNode::Bytes(bytes) => parse_from_slice(bytes),
+            _ => Err(Error::new(
+                ErrorKind::Custom("not a byte string".into()),
+                Error::NO_POSITION,
+            )),
+        }
+    }
+
+    /// Wraps the encoding of `inner` in a byte string, the reverse of
+    /// [`BNode::decode_nested_bencode`].
+    pub fn encode_nested_bencode(inner: &BNode) -> BNode {
+        BNode::Bytes(inner.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_from_str, BNode, ErrorKind};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_encode_length_delimited() {
+        let bnode = parse_from_str("d1:md11:ut_metadatai3eee").unwrap();
+
+        let mut buf = vec![];
+        assert_eq!(28, bnode.encode_length_delimited(&mut buf).unwrap());
+        assert_eq!(b"\x00\x00\x00\x18d1:md11:ut_metadatai3eee", &buf[..]);
+
+        BNode::Integer(7).encode_length_delimited(&mut buf).unwrap();
+        let mut cursor = Cursor::new(buf);
+        assert_eq!(bnode, BNode::decode_length_delimited(&mut cursor).unwrap());
+        assert_eq!(
+            BNode::Integer(7),
+            BNode::decode_length_delimited(&mut cursor).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_length_delimited_failed() {
+        let err = BNode::decode_length_delimited(&mut Cursor::new(b"\x00\x00")).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Io(_)));
+
+        let err =
+            BNode::decode_length_delimited(&mut Cursor::new(b"\x00\x00\x00\x05i1e")).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Io(_)));
+
+        let err =
+            BNode::decode_length_delimited(&mut Cursor::new(b"\x00\x00\x00\x03i1x")).unwrap_err();
+        assert_eq!(&ErrorKind::InvalidToken(b'x'), err.kind());
+        assert_eq!(6, err.position);
+    }
+
+    #[test]
+    fn test_nested_bencode() {
+        let inner = parse_from_str("d8:msg_typei1e5:piecei0ee").unwrap();
+
+        let nested = BNode::encode_nested_bencode(&inner);
+        assert_eq!(BNode::from("d8:msg_typei1e5:piecei0ee"), nested);
+        assert_eq!(inner, nested.decode_nested_bencode().unwrap());
+
+        let mut outer = BNode::from(crate::BDict::new());
+        outer.insert_entry("m".into(), nested).unwrap();
+        let outer = parse_from_str(&outer.to_string()).unwrap();
+        assert_eq!(inner, outer["m"].decode_nested_bencode().unwrap());
+    }
+
+    #[test]
+    fn test_nested_bencode_failed() {
+        let err = BNode::Integer(1).decode_nested_bencode().unwrap_err();
+        assert_eq!(&ErrorKind::Custom("not a byte string".into()), err.kind());
+
+        let err = BNode::from("li1ex").decode_nested_bencode().unwrap_err();
+        assert_eq!(&ErrorKind::InvalidToken(b'x'), err.kind());
+        assert_eq!(4, err.position);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::BNode;
+use core::convert::Infallible;
+use digest::Update;
+
+/// Feeds whatever is written into a hasher, e.g. to hash an encoding passed
+/// to [`BNode::serialize`] or copied from a reader with [`std::io::copy`].
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone)]
+pub struct DigestWriter<D> {
+    hasher: D,
+}
+
+#[cfg(feature = "std")]
+impl<D: Update> DigestWriter<D> {
+    pub fn new(hasher: D) -> DigestWriter<D> {
+        DigestWriter { hasher }
+    }
+
+    /// Returns the hasher, fed with everything written so far.
+    pub fn into_inner(self) -> D {
+        self.hasher
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: Update> std::io::Write for DigestWriter<D> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl BNode {
+    /// Feeds the canonical encoding into `hasher` without buffering it.
+    pub fn hash_with<D: Update>(&self, hasher: &mut D) {
+        let _ = self.encode(|bytes| {
+            hasher.update(bytes);
+            Ok::<_, Infallible>(())
+        });
+    }
+
+    /// Returns the SHA-1 digest of the canonical encoding, e.g. the info hash
+    /// when called on a torrent's `info` dictionary.
+    #[cfg(feature = "sha1")]
+    pub fn sha1_bytes(&self) -> [u8; 20] {
+        use sha1::Digest;
+
+        let mut hasher = sha1::Sha1::new();
+        self.hash_with(&mut hasher);
+        hasher.finalize().into()
+    }
+}
+
+#[cfg(all(test, feature = "sha1"))]
+mod tests {
+    use super::DigestWriter;
+    use crate::parse_from_str;
+    use crate::tests::DEBIAN_TORRENT;
+    use sha1::{Digest, Sha1};
+
+    #[test]
+    fn test_sha1_bytes() {
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+
+        // The fixture's `info` dictionary lacks `pieces`, so this is the SHA-1
+        // of that truncated dictionary, not the torrent's public info hash.
+        let expected: [u8; 20] = [
+            0xd1, 0x45, 0xf0, 0xab, 0xcb, 0x15, 0x71, 0x0d, 0x4c, 0xb7, 0xd2, 0xb2, 0xfa, 0xa3,
+            0xb4, 0xb8, 0x23, 0xac, 0xa9, 0xfe,
+        ];
+        assert_eq!(expected, torrent["info"].sha1_bytes());
+    }
+
+    #[test]
+    fn test_hash_with() {
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+
+        let mut hasher = Sha1::new();
+        torrent.hash_with(&mut hasher);
+        assert_eq!(
+            Sha1::digest(torrent.to_bytes()).as_slice(),
+            hasher.finalize().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_digest_writer() {
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+
+        let mut writer = DigestWriter::new(Sha1::new());
+        let written = torrent["info"].serialize(&mut writer).unwrap();
+        assert_eq!(torrent["info"].encoded_len(), written);
+        assert_eq!(
+            torrent["info"].sha1_bytes(),
+            <[u8; 20]>::from(writer.into_inner().finalize())
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::prelude::*;
+use crate::{BNode, ErrorKind, Result};
+use core::fmt::Write;
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn write_hex<W: Write>(f: &mut W, bytes: &[u8]) -> core::fmt::Result {
+    for byte in bytes {
+        f.write_char(DIGITS[(byte >> 4) as usize] as char)?;
+        f.write_char(DIGITS[(byte & 0xf) as usize] as char)?;
+    }
+
+    Ok(())
+}
+
+fn nibble(digit: u8, position: usize) -> Result<u8> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => throw!(ErrorKind::InvalidToken(digit), position as u64),
+    }
+}
+
+impl BNode {
+    /// Hex-encodes the content of a byte string in lower case.
+    pub fn to_hex(&self) -> core::result::Result<String, String> {
+        let bytes = self.as_bytes()?;
+        let mut hex = String::with_capacity(bytes.len() * 2);
+        write_hex(&mut hex, bytes).unwrap();
+
+        Ok(hex)
+    }
+
+    /// Decodes a hex string of either case into a byte string. Errors are
+    /// positioned at the offending digit, or at the end of an odd-length input.
+    pub fn from_hex(hex: &str) -> Result<BNode> {
+        let digits = hex.as_bytes();
+        if !digits.len().is_multiple_of(2) {
+            throw!(ErrorKind::UnexpectedEof, digits.len() as u64)
+        }
+
+        let mut bytes = Vec::with_capacity(digits.len() / 2);
+        for (i, pair) in digits.chunks(2).enumerate() {
+            bytes.push(nibble(pair[0], i * 2)? << 4 | nibble(pair[1], i * 2 + 1)?);
+        }
+
+        Ok(BNode::Bytes(bytes))
+    }
+}
+
+/// Writes the content of a byte string in hex, any other node is written as
+/// its encoded form in hex.
+impl core::fmt::LowerHex for BNode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BNode::Bytes(bytes) => write_hex(f, bytes),
+            node => write_hex(f, &node.to_bytes()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BNode, ErrorKind};
+
+    #[test]
+    fn test_hex_round_trip() {
+        let hash = BNode::from_hex("d145f0abcb15710d4cb7d2b2faa3b4b823aca9fe").unwrap();
+        assert_eq!(20, hash.len());
+
+        let hex = hash.to_hex().unwrap();
+        assert_eq!("d145f0abcb15710d4cb7d2b2faa3b4b823aca9fe", hex);
+        assert_eq!(hash, BNode::from_hex(&hex).unwrap());
+        assert_eq!(hash, BNode::from_hex(&hex.to_uppercase()).unwrap());
+    }
+
+    #[test]
+    fn test_hex_failed() {
+        let err = BNode::from_hex("abc").unwrap_err();
+        assert_eq!((3, &ErrorKind::UnexpectedEof), (err.position, err.kind()));
+
+        let err = BNode::from_hex("abzd").unwrap_err();
+        assert_eq!(
+            (2, &ErrorKind::InvalidToken(b'z')),
+            (err.position, err.kind())
+        );
+
+        assert_eq!(
+            Err("expected bytes, found integer".to_string()),
+            BNode::Integer(1).to_hex()
+        );
+    }
+
+    #[test]
+    fn test_lower_hex() {
+        assert_eq!("00ff", format!("{:x}", BNode::Bytes(vec![0, 255])));
+        assert_eq!("693165", format!("{:x}", BNode::Integer(1)));
+        assert_eq!("", format!("{:x}", BNode::from("")));
+    }
+}
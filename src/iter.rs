@@ -0,0 +1,183 @@
+use crate::prelude::*;
+use crate::{BNode, BNodeType};
+
+/// Consuming iterator over the elements of a list node, created by
+/// [`BNode::into_list_iter`].
+#[derive(Debug)]
+pub struct BListIter {
+    inner: alloc::vec::IntoIter<BNode>,
+}
+
+impl Iterator for BListIter {
+    type Item = BNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for BListIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl ExactSizeIterator for BListIter {}
+
+/// Consuming iterator over the entries of a dictionary node in key order, or
+/// in insertion order for an ordered one, created by [`BNode::into_dict_iter`].
+#[derive(Debug)]
+pub struct BDictIter {
+    inner: DictIntoIter,
+}
+
+#[derive(Debug)]
+enum DictIntoIter {
+    Sorted(alloc::collections::btree_map::IntoIter<String, BNode>),
+    #[cfg(feature = "indexmap")]
+    Ordered(indexmap::map::IntoIter<String, BNode>),
+}
+
+impl Iterator for BDictIter {
+    type Item = (String, BNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            DictIntoIter::Sorted(iter) => iter.next(),
+            #[cfg(feature = "indexmap")]
+            DictIntoIter::Ordered(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            DictIntoIter::Sorted(iter) => iter.size_hint(),
+            #[cfg(feature = "indexmap")]
+            DictIntoIter::Ordered(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for BDictIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            DictIntoIter::Sorted(iter) => iter.next_back(),
+            #[cfg(feature = "indexmap")]
+            DictIntoIter::Ordered(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl ExactSizeIterator for BDictIter {}
+
+impl BNode {
+    pub fn into_list_iter(self) -> core::result::Result<BListIter, String> {
+        match self {
+            BNode::List(list) => Ok(BListIter {
+                inner: list.into_iter(),
+            }),
+            _ => Err(self.type_mismatch(BNodeType::List)),
+        }
+    }
+
+    pub fn into_dict_iter(self) -> core::result::Result<BDictIter, String> {
+        match self {
+            BNode::Dict(dict) => Ok(BDictIter {
+                inner: DictIntoIter::Sorted(dict.into_iter()),
+            }),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Ok(BDictIter {
+                inner: DictIntoIter::Ordered(dict.into_iter()),
+            }),
+            _ => Err(self.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    pub fn iter_list(&self) -> core::result::Result<impl Iterator<Item = &BNode>, String> {
+        Ok(self.as_list()?.iter())
+    }
+
+    /// Iterates in key order, or in insertion order for an ordered dictionary.
+    pub fn iter_dict(&self) -> core::result::Result<impl Iterator<Item = (&str, &BNode)>, String> {
+        let entries: Box<dyn Iterator<Item = (&String, &BNode)>> = match self {
+            BNode::Dict(dict) => Box::new(dict.iter()),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Box::new(dict.iter()),
+            _ => return Err(self.type_mismatch(BNodeType::Dict)),
+        };
+        Ok(entries.map(|(k, v)| (k.as_str(), v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_from_str, BNode};
+
+    #[test]
+    fn test_into_list_iter() {
+        let bnode = parse_from_str("li1e4:spame").unwrap();
+
+        let mut iter = bnode.into_list_iter().unwrap();
+        assert_eq!(2, iter.len());
+        assert_eq!(Some(BNode::Integer(1)), iter.next());
+        assert_eq!(Some(BNode::from("spam")), iter.next());
+        assert_eq!(None, iter.next());
+
+        assert!(BNode::Integer(1).into_list_iter().is_err());
+    }
+
+    #[test]
+    fn test_into_dict_iter() {
+        let bnode = parse_from_str("d3:fooi1e3:bari2ee").unwrap();
+
+        let entries: Vec<_> = bnode.into_dict_iter().unwrap().collect();
+        assert_eq!(
+            vec![
+                ("bar".to_string(), BNode::Integer(2)),
+                ("foo".to_string(), BNode::Integer(1))
+            ],
+            entries
+        );
+
+        assert!(BNode::List(vec![]).into_dict_iter().is_err());
+    }
+
+    #[test]
+    fn test_iter_borrowed() {
+        let list = parse_from_str("li1ei2ee").unwrap();
+        let sum: i64 = list
+            .iter_list()
+            .unwrap()
+            .map(|n| n.as_integer().unwrap())
+            .sum();
+        assert_eq!(3, sum);
+
+        let dict = parse_from_str("d3:fooi1e3:bari2ee").unwrap();
+        let keys: Vec<&str> = dict.iter_dict().unwrap().map(|(k, _)| k).collect();
+        assert_eq!(vec!["bar", "foo"], keys);
+
+        assert!(list.iter_dict().is_err());
+        assert!(dict.iter_list().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_iter_ordered_dict() {
+        let raw = "d3:fooi1e3:bari2ee";
+        let dict = crate::parse_ordered(&mut raw.bytes()).unwrap();
+
+        let keys: Vec<&str> = dict.iter_dict().unwrap().map(|(k, _)| k).collect();
+        assert_eq!(vec!["foo", "bar"], keys);
+        let mut iter = dict.into_dict_iter().unwrap();
+        assert_eq!(2, iter.len());
+        assert_eq!(
+            Some(("bar".to_string(), BNode::Integer(2))),
+            iter.next_back()
+        );
+        assert_eq!(Some(("foo".to_string(), BNode::Integer(1))), iter.next());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{BDict, BNode};
+use serde_json::{Map, Value};
+
+/// Marks a JSON string holding a base64 encoded byte string.
+const BASE64_PREFIX: &str = "$b64:";
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn decode_base64(encoded: &str) -> Result<Vec<u8>, String> {
+    let encoded = encoded.trim_end_matches('=').as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+
+    for chunk in encoded.chunks(4) {
+        if chunk.len() == 1 {
+            return Err("truncated base64".into());
+        }
+
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64_ALPHABET
+                .iter()
+                .position(|a| a == c)
+                .ok_or_else(|| format!("invalid base64 character `{}`", *c as char))?;
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+
+    Ok(bytes)
+}
+
+impl BNode {
+    /// Converts into JSON. Byte strings which are valid UTF-8 become plain
+    /// strings, any other is base64 encoded behind a `$b64:` prefix. Big
+    /// integers beyond the `u64` range become their decimal string.
+    pub fn to_json(&self) -> Value {
+        match self {
+            BNode::Integer(i) => Value::Number((*i).into()),
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(i) => match u64::try_from(&**i) {
+                Ok(i) => Value::Number(i.into()),
+                Err(_) => Value::String(i.to_string()),
+            },
+            BNode::Bytes(bytes) => match core::str::from_utf8(bytes) {
+                Ok(s) => Value::String(s.to_string()),
+                Err(_) => Value::String(format!("{}{}", BASE64_PREFIX, encode_base64(bytes))),
+            },
+            BNode::List(list) => Value::Array(list.iter().map(BNode::to_json).collect()),
+            BNode::Dict(dict) => Value::Object(
+                dict.iter()
+                    .map(|(k, v)| (k.clone(), v.to_json()))
+                    .collect::<Map<_, _>>(),
+            ),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Value::Object(
+                dict.iter()
+                    .map(|(k, v)| (k.clone(), v.to_json()))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+
+    /// The reverse of [`BNode::to_json`], booleans become `0` or `1` and
+    /// `null` an empty byte string, while floats and numbers out of the `i64`
+    /// range are rejected.
+    ///
+    /// The round-trip is lossy: a UTF-8 byte string which happens to start
+    /// with `$b64:` comes back decoded from base64.
+    pub fn from_json(value: Value) -> Result<BNode, String> {
+        Ok(match value {
+            Value::Null => BNode::Bytes(vec![]),
+            Value::Bool(b) => b.into(),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => BNode::Integer(i),
+                None => return Err(format!("{} is not an i64", n)),
+            },
+            Value::String(s) => match s.strip_prefix(BASE64_PREFIX) {
+                Some(encoded) => BNode::Bytes(decode_base64(encoded)?),
+                None => s.into(),
+            },
+            Value::Array(values) => values
+                .into_iter()
+                .map(BNode::from_json)
+                .collect::<Result<Vec<_>, _>>()?
+                .into(),
+            Value::Object(map) => map
+                .into_iter()
+                .map(|(k, v)| Ok((k, BNode::from_json(v)?)))
+                .collect::<Result<BDict, String>>()?
+                .into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_base64, encode_base64};
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{parse_from_str, BNode};
+    use serde_json::json;
+
+    #[test]
+    fn test_to_json() {
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+        let json = torrent.to_json();
+
+        assert_eq!(
+            json!("http://bttracker.debian.org:6969/announce"),
+            json["announce"]
+        );
+        assert_eq!(json!(351272960), json["info"]["length"]);
+        assert_eq!(2, json["httpseeds"].as_array().unwrap().len());
+        assert_eq!(Ok(torrent), BNode::from_json(json));
+    }
+
+    #[test]
+    fn test_json_binary_bytes() {
+        let bnode = BNode::Bytes(vec![0xff, 0x00, 0xfe, 0x01]);
+
+        let json = bnode.to_json();
+        assert_eq!(json!("$b64:/wD+AQ=="), json);
+        assert_eq!(Ok(bnode), BNode::from_json(json));
+    }
+
+    #[test]
+    fn test_from_json_rejected() {
+        assert!(BNode::from_json(json!(1.5)).is_err());
+        assert!(BNode::from_json(json!(u64::MAX)).is_err());
+        let below_min = serde_json::from_str("-9223372036854775809").unwrap();
+        assert!(BNode::from_json(below_min).is_err());
+        assert!(BNode::from_json(json!(["$b64:*"])).is_err());
+        assert!(BNode::from_json(json!({"a": [0.5]})).is_err());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = json!({
+            "b": [1, "spam", null],
+            "a": {"flag": true, "off": false},
+            "min": i64::MIN,
+        });
+
+        assert_eq!(
+            "d1:ad4:flagi1e3:offi0ee1:bli1e4:spam0:e3:mini-9223372036854775808ee",
+            BNode::from_json(json).unwrap().to_string()
+        );
+        assert_eq!(Ok(BNode::Bytes(vec![])), BNode::from_json(json!(null)));
+    }
+
+    #[test]
+    fn test_base64() {
+        for raw in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            assert_eq!(Ok(raw.to_vec()), decode_base64(&encode_base64(raw)));
+        }
+        assert_eq!("Zm9vYg==", encode_base64(b"foob"));
+    }
+}
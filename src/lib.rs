@@ -1,160 +1,576 @@
-mod extensions;
-
-use std::{fmt::Display, io::Write};
-pub type BList = Vec<BNode>;
-pub type BDict = std::collections::BTreeMap<String, BNode>;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#[derive(Debug)]
-pub struct Error {
-    pub position: i64,
-    pub msg: String,
-}
+extern crate alloc;
 
+// Declared ahead of the modules so that they can use it as well.
 macro_rules! throw {
-    ($msg:expr, $pos:expr) => {
-        return Err(Error {
-            msg: $msg.into(),
-            position: $pos,
-        })
+    ($kind:expr, $pos:expr) => {
+        return Err(crate::Error::new($kind, $pos))
     };
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+/// The items of the std prelude which come from `alloc`, for modules to read
+/// the same with and without the `std` feature.
+#[allow(unused_imports)]
+mod prelude {
+    pub(crate) use alloc::borrow::ToOwned;
+    pub(crate) use alloc::boxed::Box;
+    pub(crate) use alloc::string::{String, ToString};
+    pub(crate) use alloc::vec::Vec;
+    pub(crate) use alloc::{format, vec};
+}
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg(feature = "tokio")]
+pub mod async_io;
+mod borrowed;
+mod builder;
+mod bytes;
+mod dict;
+mod diff;
+mod error;
+mod events;
+mod extensions;
+#[cfg(feature = "std")]
+mod framing;
+#[cfg(feature = "digest")]
+mod hashing;
+mod hex;
+mod iter;
+#[cfg(feature = "json")]
+mod json;
+mod list;
+mod macros;
+mod node_type;
+mod path;
+mod pretty;
+mod query;
+mod raw;
+mod reader;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde_format;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod spanned;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+#[cfg(feature = "torrent")]
+pub mod torrent;
+mod traverse;
+
+use alloc::collections::VecDeque;
+#[cfg(feature = "derive")]
+pub use bencodex_derive::{FromBNode, ToBNode};
+pub use borrowed::{parse_borrowed, BorrowedBNode};
+pub use builder::{BDictBuilder, BListBuilder};
+pub use bytes::BBytes;
+use core::{convert::Infallible, fmt::Display};
+pub use diff::{BNodeDiff, KeyDiff};
+#[cfg(feature = "std")]
+pub use error::BencodeError;
+pub use error::{Error, ErrorKind};
+pub use events::{BNodeEvents, Event};
+#[cfg(all(feature = "digest", feature = "std"))]
+pub use hashing::DigestWriter;
+pub use iter::{BDictIter, BListIter};
+pub use node_type::BNodeType;
+pub use path::PathStep;
+use prelude::*;
+pub use pretty::PrettyDisplay;
+pub use raw::{parse_raw, BDictRaw, BNodeRaw};
+pub use spanned::{parse_with_spans, BNodeSpanned};
+pub use traverse::NodeTypeCounts;
+
+/// Paths used by the code generated by the derive macros, which can't name
+/// `alloc` in a `no_std` crate that doesn't declare it.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::format;
+    pub use alloc::string::String;
+}
+
+pub type BList = Vec<BNode>;
+/// Being a `BTreeMap`, a dictionary can't hold duplicate keys and iterates in
+/// key order, so every `BNode` serializes canonically however it was built.
+/// Only a [`BNode::OrderedDict`] keeps another order in memory, see
+/// [`BNode::normalize`].
+pub type BDict = alloc::collections::BTreeMap<String, BNode>;
+/// A dictionary keeping its keys in insertion order, see [`parse_ordered`].
+#[cfg(feature = "indexmap")]
+pub type BOrderedDict = indexmap::IndexMap<String, BNode>;
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+/// The result of operations which may fail both parsing and doing I/O.
+#[cfg(feature = "std")]
+pub type IoResult<T> = core::result::Result<T, BencodeError>;
+
+#[derive(PartialEq, Eq, Clone)]
 pub enum BNode {
     Integer(i64),
+    /// An integer beyond the range of `i64`, which the parser produces
+    /// instead of failing with [`ErrorKind::IntegerOverflow`].
+    #[cfg(feature = "bigint")]
+    BigInteger(Box<num_bigint::BigInt>),
+    /// A dictionary in the order its keys were inserted or parsed. It is
+    /// still encoded with its keys sorted.
+    #[cfg(feature = "indexmap")]
+    OrderedDict(BOrderedDict),
     Bytes(Vec<u8>),
     List(BList),
     Dict(BDict),
 }
 
+/// The entries of an ordered dictionary in the order they are encoded in.
+#[cfg(feature = "indexmap")]
+pub(crate) fn sorted_entries(dict: &BOrderedDict) -> Vec<(&String, &BNode)> {
+    let mut entries: Vec<_> = dict.iter().collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+    entries
+}
+
+#[allow(clippy::enum_variant_names)]
+enum MarshalWork<'a> {
+    WriteNode(&'a BNode),
+    WriteBytes(&'a [u8]),
+    /// Writes the `<length>:` prefix of a dictionary key.
+    WriteLength(usize),
+}
+
 impl BNode {
-    pub fn serialize<W>(&self, buf: &mut W) -> std::io::Result<usize>
+    /// Passes the encoding piece by piece to `sink`, returning the number of
+    /// bytes passed.
+    fn encode<E, F>(&self, mut sink: F) -> core::result::Result<usize, E>
     where
-        W: Write,
+        F: FnMut(&[u8]) -> core::result::Result<(), E>,
     {
+        let mut write = |bytes: &[u8]| sink(bytes).map(|_| bytes.len());
+        // Pending output is kept on the heap instead of the call stack, so
+        // deeply nested nodes cannot overflow it.
+        let mut work = VecDeque::from([MarshalWork::WriteNode(self)]);
         let mut w = 0;
-        match self {
-            BNode::Integer(i) => {
-                w += buf.write(b"i")?;
-                w += buf.write(i.to_string().as_bytes())?;
-                w += buf.write(b"e")?;
-            }
-            BNode::Bytes(s) => {
-                w += buf.write(s.len().to_string().as_bytes())?;
-                w += buf.write(b":")?;
-                w += buf.write(s)?;
-            }
-            BNode::List(l) => {
-                w += buf.write(b"l")?;
-                for bn in l {
-                    w += bn.serialize(buf)?;
+
+        while let Some(item) = work.pop_front() {
+            match item {
+                MarshalWork::WriteBytes(bytes) => {
+                    w += write(bytes)?;
                 }
-                w += buf.write(b"e")?;
-            }
-            BNode::Dict(m) => {
-                w += buf.write(b"d")?;
-                for (k, v) in m {
-                    w += buf.write(k.len().to_string().as_bytes())?;
-                    w += buf.write(b":")?;
-                    w += buf.write(k.as_bytes())?;
-                    w += v.serialize(buf)?;
+                MarshalWork::WriteLength(len) => {
+                    w += write(len.to_string().as_bytes())?;
+                    w += write(b":")?;
+                }
+                MarshalWork::WriteNode(BNode::Integer(i)) => {
+                    w += write(b"i")?;
+                    w += write(i.to_string().as_bytes())?;
+                    w += write(b"e")?;
+                }
+                #[cfg(feature = "bigint")]
+                MarshalWork::WriteNode(BNode::BigInteger(i)) => {
+                    w += write(b"i")?;
+                    w += write(i.to_string().as_bytes())?;
+                    w += write(b"e")?;
+                }
+                MarshalWork::WriteNode(BNode::Bytes(s)) => {
+                    w += write(s.len().to_string().as_bytes())?;
+                    w += write(b":")?;
+                    w += write(s)?;
+                }
+                MarshalWork::WriteNode(BNode::List(l)) => {
+                    w += write(b"l")?;
+                    work.push_front(MarshalWork::WriteBytes(b"e"));
+                    for bn in l.iter().rev() {
+                        work.push_front(MarshalWork::WriteNode(bn));
+                    }
+                }
+                MarshalWork::WriteNode(BNode::Dict(m)) => {
+                    w += write(b"d")?;
+                    work.push_front(MarshalWork::WriteBytes(b"e"));
+                    for (k, v) in m.iter().rev() {
+                        work.push_front(MarshalWork::WriteNode(v));
+                        work.push_front(MarshalWork::WriteBytes(k.as_bytes()));
+                        work.push_front(MarshalWork::WriteLength(k.len()));
+                    }
+                }
+                #[cfg(feature = "indexmap")]
+                MarshalWork::WriteNode(BNode::OrderedDict(m)) => {
+                    w += write(b"d")?;
+                    work.push_front(MarshalWork::WriteBytes(b"e"));
+                    for (k, v) in sorted_entries(m).into_iter().rev() {
+                        work.push_front(MarshalWork::WriteNode(v));
+                        work.push_front(MarshalWork::WriteBytes(k.as_bytes()));
+                        work.push_front(MarshalWork::WriteLength(k.len()));
+                    }
                 }
-                w += buf.write(b"e")?;
             }
         }
 
         Ok(w)
     }
 
-    pub fn as_integer(&self) -> std::result::Result<&i64, String> {
+    #[cfg(feature = "std")]
+    pub fn serialize<W>(&self, buf: &mut W) -> IoResult<usize>
+    where
+        W: std::io::Write,
+    {
+        Ok(self.encode(|bytes| buf.write_all(bytes))?)
+    }
+
+    /// Returns the number of bytes `serialize` writes, without encoding.
+    pub fn encoded_len(&self) -> usize {
+        let mut len = 0;
+
+        self.walk(|node| {
+            len += match node {
+                BNode::Integer(i) => 2 + (*i < 0) as usize + decimal_digit_count(i.unsigned_abs()),
+                #[cfg(feature = "bigint")]
+                BNode::BigInteger(i) => 2 + i.to_string().len(),
+                BNode::Bytes(s) => decimal_digit_count(s.len() as u64) + 1 + s.len(),
+                BNode::List(_) => 2,
+                BNode::Dict(m) => {
+                    2 + m
+                        .keys()
+                        .map(|k| decimal_digit_count(k.len() as u64) + 1 + k.len())
+                        .sum::<usize>()
+                }
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(m) => {
+                    2 + m
+                        .keys()
+                        .map(|k| decimal_digit_count(k.len() as u64) + 1 + k.len())
+                        .sum::<usize>()
+                }
+            }
+        });
+
+        len
+    }
+
+    /// Encodes into a new `Vec`, available without the `std` feature.
+    pub fn marshal_to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        let _ = self.encode(|bytes| {
+            buf.extend_from_slice(bytes);
+            Ok::<_, Infallible>(())
+        });
+        buf
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.marshal_to_vec()
+    }
+
+    /// Returns the number of bytes, elements or entries, an integer has no
+    /// length and always returns `0`.
+    pub fn len(&self) -> usize {
+        match self {
+            BNode::Integer(_) => 0,
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(_) => 0,
+            BNode::Bytes(bytes) => bytes.len(),
+            BNode::List(list) => list.len(),
+            BNode::Dict(dict) => dict.len(),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => dict.len(),
+        }
+    }
+
+    /// Returns `true` for empty bytes, list or dictionary, an integer is never
+    /// empty.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            BNode::Integer(_) => false,
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(_) => false,
+            _ => self.len() == 0,
+        }
+    }
+
+    /// Returns `true` for big integers too.
+    #[inline]
+    pub fn is_integer(&self) -> bool {
+        match self {
+            BNode::Integer(_) => true,
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(_) => true,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, BNode::Bytes(_))
+    }
+
+    #[inline]
+    pub fn is_list(&self) -> bool {
+        matches!(self, BNode::List(_))
+    }
+
+    /// Returns `true` for ordered dictionaries too.
+    #[inline]
+    pub fn is_dict(&self) -> bool {
+        match self {
+            BNode::Dict(_) => true,
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn as_integer(&self) -> core::result::Result<&i64, String> {
+        match self {
+            BNode::Integer(value) => Ok(value),
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(_) => Err("integer out of the i64 range".into()),
+            _ => Err(self.type_mismatch(BNodeType::Integer)),
+        }
+    }
+
+    /// Returns the value of a [`BNode::BigInteger`], integers within the
+    /// `i64` range are read with [`BNode::as_integer`] instead.
+    #[cfg(feature = "bigint")]
+    pub fn as_bigint(&self) -> core::result::Result<&num_bigint::BigInt, String> {
+        match self {
+            BNode::BigInteger(value) => Ok(value),
+            _ => Err(format!("expected big integer, found {}", self.type_name())),
+        }
+    }
+
+    pub fn as_bytes(&self) -> core::result::Result<&[u8], String> {
+        match self {
+            BNode::Bytes(bytes) => Ok(bytes),
+            _ => Err(self.type_mismatch(BNodeType::Bytes)),
+        }
+    }
+
+    pub fn as_str(&self) -> core::result::Result<&str, String> {
+        match self {
+            BNode::Bytes(bytes) => core::str::from_utf8(bytes).map_err(|e| e.to_string()),
+            _ => Err(self.type_mismatch(BNodeType::Bytes)),
+        }
+    }
+
+    pub fn as_string(&self) -> core::result::Result<String, String> {
+        self.as_str().map(String::from)
+    }
+
+    /// Like [`String::from_utf8_lossy`], borrows the bytes if they are valid
+    /// UTF-8 and replaces invalid sequences with `U+FFFD` otherwise.
+    pub fn as_utf8_lossy(&self) -> core::result::Result<alloc::borrow::Cow<'_, str>, String> {
+        match self {
+            BNode::Bytes(bytes) => Ok(String::from_utf8_lossy(bytes)),
+            _ => Err(self.type_mismatch(BNodeType::Bytes)),
+        }
+    }
+
+    pub fn as_list(&self) -> core::result::Result<&[BNode], String> {
+        match self {
+            BNode::List(list) => Ok(list),
+            _ => Err(self.type_mismatch(BNodeType::List)),
+        }
+    }
+
+    /// Fails for an ordered dictionary, which isn't a [`BDict`]. The helpers
+    /// such as [`BNode::get`] or [`BNode::iter_dict`] accept both.
+    pub fn as_dict(&self) -> core::result::Result<&BDict, String> {
+        match self {
+            BNode::Dict(dict) => Ok(dict),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(_) => Err("expected dict, found ordered dict".into()),
+            _ => Err(self.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    pub fn as_integer_mut(&mut self) -> core::result::Result<&mut i64, String> {
         match self {
             BNode::Integer(value) => Ok(value),
-            _ => Err("not an integer".into()),
+            _ => Err(self.type_mismatch(BNodeType::Integer)),
         }
     }
 
-    pub fn as_bytes(&self) -> std::result::Result<&[u8], String> {
+    pub fn as_bytes_mut(&mut self) -> core::result::Result<&mut Vec<u8>, String> {
         match self {
             BNode::Bytes(bytes) => Ok(bytes),
-            _ => Err("not a byte array".into()),
+            _ => Err(self.type_mismatch(BNodeType::Bytes)),
         }
     }
 
-    pub fn as_list(&self) -> std::result::Result<&[BNode], String> {
+    pub fn as_list_mut(&mut self) -> core::result::Result<&mut BList, String> {
         match self {
             BNode::List(list) => Ok(list),
-            _ => Err("not a list".into()),
+            _ => Err(self.type_mismatch(BNodeType::List)),
         }
     }
 
-    pub fn as_dict(&self) -> std::result::Result<&BDict, String> {
+    /// Fails for an ordered dictionary like [`BNode::as_dict`].
+    pub fn as_dict_mut(&mut self) -> core::result::Result<&mut BDict, String> {
         match self {
             BNode::Dict(dict) => Ok(dict),
-            _ => Err("not a dictionary".into()),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(_) => Err("expected dict, found ordered dict".into()),
+            _ => Err(self.type_mismatch(BNodeType::Dict)),
+        }
+    }
+
+    pub fn into_integer(self) -> core::result::Result<i64, BNode> {
+        match self {
+            BNode::Integer(value) => Ok(value),
+            _ => Err(self),
+        }
+    }
+
+    pub fn into_bytes(self) -> core::result::Result<Vec<u8>, BNode> {
+        match self {
+            BNode::Bytes(bytes) => Ok(bytes),
+            _ => Err(self),
+        }
+    }
+
+    pub fn into_list(self) -> core::result::Result<BList, BNode> {
+        match self {
+            BNode::List(list) => Ok(list),
+            _ => Err(self),
+        }
+    }
+
+    /// An ordered dictionary is converted, losing the order of its keys.
+    pub fn into_dict(self) -> core::result::Result<BDict, BNode> {
+        match self {
+            BNode::Dict(dict) => Ok(dict),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => Ok(dict.into_iter().collect()),
+            _ => Err(self),
         }
     }
 }
 
 impl Display for BNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        let mut buf = vec![];
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::result::Result<(), core::fmt::Error> {
+        // Invalid UTF-8 sequences are percent-escaped instead of panicking,
+        // and so is `%` itself to keep the escaping reversible.
+        for chunk in self.to_bytes().utf8_chunks() {
+            for (i, text) in chunk.valid().split('%').enumerate() {
+                if i > 0 {
+                    f.write_str("%25")?;
+                }
+                f.write_str(text)?;
+            }
+            for byte in chunk.invalid() {
+                write!(f, "%{:02X}", byte)?;
+            }
+        }
 
-        self.serialize(&mut buf).unwrap();
-        write!(f, "{}", std::str::from_utf8(&buf).unwrap())
+        Ok(())
     }
 }
 
-/// https://en.wikipedia.org/wiki/Bencode
+fn decimal_digit_count(mut n: u64) -> usize {
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+/// The tokens of bencode, see <https://en.wikipedia.org/wiki/Bencode>.
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Token {
+pub enum Token {
+    /// `i`, followed by the digits of an integer and [`Token::End`].
     IntegerBegin,
+    /// `l`, followed by the elements and [`Token::End`].
     ListBegin,
+    /// `d`, followed by key and value pairs and [`Token::End`].
     DictBegin,
+    /// The decimal length of a byte string, followed by [`Token::Colon`] and
+    /// the bytes themselves.
     Length(i64),
+    /// `e`, closing an integer, a list or a dictionary.
     End,
+    /// `:`, separating the length of a byte string from its bytes.
     Colon,
+    /// The end of the input.
     EOF,
 }
 
+const MAX_PREALLOCATED_BYTES: usize = 64 * 1024;
+
+/// Splits a stream of bytes into [`Token`]s, for building parsers other than
+/// the ones of this crate.
+///
+/// The lexer only tokenizes the structural bytes, the digits of an integer
+/// and the content of a byte string are read with
+/// [`Lexer::read_i64_before`] and [`Lexer::read_bytes`] respectively.
 #[derive(Debug)]
-struct Lexer<'a, T>
+pub struct Lexer<'a, T>
 where
     T: Iterator<Item = u8>,
 {
     stream: &'a mut T,
-    position: i64,
+    /// Number of bytes requested from the stream so far, including a final
+    /// request which hit the end of it.
+    consumed: u64,
     cached_byte: Option<u8>,
     cached_token: Option<Token>,
+    /// Leading zeros are recorded here instead of being rejected if set.
+    warnings: Option<Vec<Error>>,
 }
 
 impl<'a, T> Lexer<'a, T>
 where
     T: Iterator<Item = u8>,
 {
-    fn new(stream: &'a mut T) -> Lexer<'a, T> {
+    pub fn new(stream: &'a mut T) -> Lexer<'a, T> {
         Lexer {
             stream,
-            position: -1,
+            consumed: 0,
             cached_byte: None,
             cached_token: None,
+            warnings: None,
         }
     }
 
+    /// The zero-based offset of the byte just consumed, `0` before any byte
+    /// has been consumed. Once the stream is exhausted it's the offset one past
+    /// the last byte.
+    pub fn position(&self) -> u64 {
+        self.consumed.saturating_sub(1)
+    }
+
     fn next_byte(&mut self) -> Option<u8> {
-        self.position += 1;
+        self.consumed += 1;
         match self.cached_byte {
             Some(_) => self.cached_byte.take(),
             None => self.stream.next(),
         }
     }
 
-    fn read_i64_before(&mut self, init: i64, symbol: u8) -> Result<(i64, i64)> {
+    /// Reads a decimal integer up to `symbol`, which is left unconsumed.
+    /// `init` holds the digits read so far. Returns the value along with the
+    /// number of digits read, the sign excluded.
+    pub fn read_i64_before(&mut self, init: i64, symbol: u8) -> Result<(i64, i64)> {
+        match self.read_integer_before(init, symbol, false)? {
+            (BNode::Integer(value), digits) => Ok((value, digits)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Like [`Lexer::read_i64_before`], but with the `bigint` feature and
+    /// `allow_big` set, an integer overflowing `i64` is read on into a
+    /// [`BNode::BigInteger`] instead of failing.
+    #[cfg_attr(not(feature = "bigint"), allow(unused_variables))]
+    fn read_integer_before(
+        &mut self,
+        init: i64,
+        symbol: u8,
+        allow_big: bool,
+    ) -> Result<(BNode, i64)> {
         let mut num = init;
+        #[cfg(feature = "bigint")]
+        let mut big: Option<num_bigint::BigInt> = None;
         let mut sign = 1i64;
         let mut read = 0;
+        let mut warned = false;
 
         while let Some(x) = self.next_byte() {
             read += 1;
@@ -162,59 +578,90 @@ where
             match x {
                 b'0'..=b'9' => {
                     if x == b'0' && sign == -1 && read == 2 {
-                        throw!("Negative zero is not permitted", self.position)
+                        throw!(ErrorKind::NegativeZero, self.position())
                     }
 
                     if num == 0 && ((sign == 1 && read != 1) || (sign == -1 && read != 2)) {
-                        throw!("Leading zero is not permitted", self.position)
+                        let position = self.position();
+                        match &mut self.warnings {
+                            Some(warnings) if !warned => {
+                                warnings.push(Error::new(ErrorKind::LeadingZero, position));
+                                warned = true;
+                            }
+                            Some(_) => {}
+                            None => throw!(ErrorKind::LeadingZero, self.position()),
+                        }
                     }
 
-                    num = num * 10 + (x - b'0') as i64
-                }
-                b'-' => match sign {
-                    -1 if read != 1 => {
-                        throw!(
-                            "`-` can only appear in the head of the integer",
-                            self.position
-                        )
+                    // Negative numbers are accumulated as such, otherwise
+                    // i64::MIN couldn't be represented on the way.
+                    let digit = (x - b'0') as i64;
+                    #[cfg(feature = "bigint")]
+                    if let Some(big) = &mut big {
+                        *big = &*big * 10 + sign * digit;
+                        continue;
                     }
-                    _ => sign = -1,
-                },
+                    num = match num.checked_mul(10).and_then(|num| match sign {
+                        1 => num.checked_add(digit),
+                        _ => num.checked_sub(digit),
+                    }) {
+                        Some(num) => num,
+                        #[cfg(feature = "bigint")]
+                        None if allow_big => {
+                            big = Some(num_bigint::BigInt::from(num) * 10 + sign * digit);
+                            continue;
+                        }
+                        None => throw!(ErrorKind::IntegerOverflow, self.position()),
+                    }
+                }
+                b'-' if read == 1 && num == 0 => sign = -1,
                 b if b == symbol => {
                     self.cached_byte = Some(symbol);
-                    self.position -= 1;
-                    return Ok((sign * num, read - 1));
+                    self.consumed -= 1;
+                    // A lone `-` doesn't count as a digit.
+                    let digits = if sign == -1 { read - 2 } else { read - 1 };
+                    #[cfg(feature = "bigint")]
+                    if let Some(big) = big {
+                        return Ok((BNode::BigInteger(Box::new(big)), digits));
+                    }
+                    return Ok((BNode::Integer(num), digits));
                 }
-                _ => throw!("invalid integer", self.position),
+                _ => throw!(ErrorKind::InvalidToken(x), self.position()),
             }
         }
 
-        throw!("invalid integer", self.position)
+        throw!(ErrorKind::UnexpectedEof, self.position())
     }
 
-    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
-        let mut ret = Vec::with_capacity(len);
+    /// Reads the `len` bytes of a byte string following its [`Token::Colon`].
+    pub fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        // The length comes from the input, so it can't be trusted to allocate
+        // everything upfront.
+        let mut ret = Vec::with_capacity(len.min(MAX_PREALLOCATED_BYTES));
 
         for _ in 0..len {
             match self.next_byte() {
                 Some(byte) => ret.push(byte),
-                None => {
-                    throw!(
-                        format!(
-                            "bytes's length is expected to be {}, but it's {}.",
-                            len,
-                            ret.len()
-                        ),
-                        self.position
-                    );
-                }
+                None => throw!(ErrorKind::UnexpectedEof, self.position()),
             }
         }
 
         Ok(ret)
     }
 
-    fn next_token(&mut self) -> Result<Token> {
+    /// Consumes `len` bytes without collecting them.
+    fn skip_bytes(&mut self, len: usize) -> Result<()> {
+        for _ in 0..len {
+            if self.next_byte().is_none() {
+                throw!(ErrorKind::UnexpectedEof, self.position());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Consumes the next token, [`Token::EOF`] once the stream is exhausted.
+    pub fn next_token(&mut self) -> Result<Token> {
         if let Some(token) = self.cached_token.take() {
             return Ok(token);
         }
@@ -227,18 +674,21 @@ where
                 b'e' => Ok(Token::End),
                 b'0'..=b'9' => {
                     // Get the bytes length until it meets the colon
-                    // TODO handle overflow?
                     let (length, _) = self.read_i64_before((unknown - b'0') as i64, b':')?;
+                    if length < 0 {
+                        throw!(ErrorKind::InvalidToken(b'-'), self.position())
+                    }
                     Ok(Token::Length(length))
                 }
                 b':' => Ok(Token::Colon),
-                _ => throw!(format!("unknown token: {}", unknown), self.position),
+                _ => throw!(ErrorKind::InvalidToken(unknown), self.position()),
             },
             None => Ok(Token::EOF),
         }
     }
 
-    fn look_ahead(&mut self) -> Result<Token> {
+    /// Returns the next token without consuming it.
+    pub fn look_ahead(&mut self) -> Result<Token> {
         if let Some(token) = &self.cached_token {
             return Ok(*token);
         }
@@ -250,11 +700,207 @@ where
     }
 }
 
+/// Options applied while parsing, a limit of `None` means unlimited.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParserConfig {
+    /// Rejects dictionaries whose keys are not sorted and unique, as required
+    /// by the canonical encoding.
+    pub strict_dict_keys: bool,
+    /// Maximum nesting level of lists and dictionaries.
+    pub max_depth: Option<usize>,
+    /// Maximum length of a single byte string.
+    pub max_bytes_len: Option<usize>,
+    /// Maximum number of elements in a single list.
+    pub max_list_elements: Option<usize>,
+    /// Maximum number of entries in a single dictionary.
+    pub max_dict_entries: Option<usize>,
+}
+
+/// Chooses which violations [`parse_lenient`] reports as warnings, `true`
+/// means lenient and `false` fatal. Everything is lenient by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LenientConfig {
+    /// Integers and lengths with leading zeros keep their value.
+    pub leading_zeros: bool,
+    /// Dictionary keys may come in any order.
+    pub unsorted_dict_keys: bool,
+    /// The last value of a repeated dictionary key wins.
+    pub duplicate_dict_keys: bool,
+}
+
+impl Default for LenientConfig {
+    fn default() -> Self {
+        LenientConfig {
+            leading_zeros: true,
+            unsorted_dict_keys: true,
+            duplicate_dict_keys: true,
+        }
+    }
+}
+
+/// Structural metrics collected by [`parse_with_stats`]. Dictionary keys are
+/// not counted as nodes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseStats {
+    /// The maximum nesting level, see [`BNode::depth`].
+    pub max_depth: usize,
+    pub nodes_parsed: usize,
+    pub bytes_consumed: u64,
+    pub integers: usize,
+    pub byte_strings: usize,
+    pub lists: usize,
+    pub dicts: usize,
+}
+
+impl ParseStats {
+    fn count(&mut self, node: &BNode) {
+        self.nodes_parsed += 1;
+        match node {
+            BNode::Integer(_) => self.integers += 1,
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(_) => self.integers += 1,
+            BNode::Bytes(_) => self.byte_strings += 1,
+            BNode::List(_) => self.lists += 1,
+            BNode::Dict(_) => self.dicts += 1,
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(_) => self.dicts += 1,
+        }
+    }
+}
+
+/// A container which is still being parsed, a dictionary also keeps the key
+/// waiting for its value.
+enum ParserFrame {
+    List(BList),
+    Dict(BDict, Option<String>),
+    #[cfg(feature = "indexmap")]
+    OrderedDict(BOrderedDict, Option<String>),
+}
+
+/// Builds the [`BNode`]s of a [`Parser`], collecting their [`ParseStats`].
+#[derive(Default)]
+struct BNodeSink {
+    stats: ParseStats,
+    depth: usize,
+    /// Dictionaries become [`BNode::OrderedDict`] keeping the keys in the order
+    /// of the input. A repeated key keeps its first position and its last
+    /// value, as it would being inserted again.
+    #[cfg(feature = "indexmap")]
+    ordered: bool,
+}
+
+impl BNodeSink {
+    fn count(&mut self, node: BNode) -> BNode {
+        self.stats.count(&node);
+        node
+    }
+
+    fn enter(&mut self) {
+        self.depth += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+    }
+}
+
+impl reader::Strings for BNodeSink {
+    type Bytes = Vec<u8>;
+    type Key = String;
+
+    fn read_bytes<T>(&mut self, lexer: &mut Lexer<'_, T>, len: usize) -> Result<Vec<u8>>
+    where
+        T: Iterator<Item = u8>,
+    {
+        lexer.read_bytes(len)
+    }
+
+    fn make_key(&mut self, bytes: Vec<u8>) -> Option<String> {
+        String::from_utf8(bytes).ok()
+    }
+}
+
+impl reader::NodeSink for BNodeSink {
+    type Node = BNode;
+    type Frame = ParserFrame;
+
+    fn integer(&mut self, value: i64, _: core::ops::Range<u64>) -> BNode {
+        self.count(BNode::Integer(value))
+    }
+
+    #[cfg(feature = "bigint")]
+    fn big_integer(&mut self, value: Box<num_bigint::BigInt>, _: core::ops::Range<u64>) -> BNode {
+        self.count(BNode::BigInteger(value))
+    }
+
+    fn bytes(&mut self, bytes: Vec<u8>, _: core::ops::Range<u64>) -> BNode {
+        self.count(BNode::Bytes(bytes))
+    }
+
+    fn list(&mut self, _: u64) -> ParserFrame {
+        self.enter();
+        ParserFrame::List(BList::new())
+    }
+
+    fn dict(&mut self, _: u64) -> ParserFrame {
+        self.enter();
+        #[cfg(feature = "indexmap")]
+        if self.ordered {
+            return ParserFrame::OrderedDict(BOrderedDict::new(), None);
+        }
+        ParserFrame::Dict(BDict::new(), None)
+    }
+
+    fn key(&mut self, frame: &mut ParserFrame, key: String) {
+        match frame {
+            ParserFrame::List(_) => unreachable!(),
+            ParserFrame::Dict(_, pending) => *pending = Some(key),
+            #[cfg(feature = "indexmap")]
+            ParserFrame::OrderedDict(_, pending) => *pending = Some(key),
+        }
+    }
+
+    fn push(&mut self, frame: &mut ParserFrame, node: BNode) {
+        match frame {
+            ParserFrame::List(list) => list.push(node),
+            ParserFrame::Dict(dict, key) => {
+                dict.insert(key.take().unwrap(), node);
+            }
+            #[cfg(feature = "indexmap")]
+            ParserFrame::OrderedDict(dict, key) => {
+                dict.insert(key.take().unwrap(), node);
+            }
+        }
+    }
+
+    fn end(&mut self, frame: ParserFrame, _: u64) -> BNode {
+        self.depth -= 1;
+        let node = match frame {
+            ParserFrame::List(list) => list.into(),
+            ParserFrame::Dict(dict, _) => dict.into(),
+            #[cfg(feature = "indexmap")]
+            ParserFrame::OrderedDict(dict, _) => BNode::OrderedDict(dict),
+        };
+        self.count(node)
+    }
+}
+
+/// Reports running out of input as such instead of as an unexpected token.
+fn unexpected(expected: &'static str, found: Token) -> ErrorKind {
+    match found {
+        Token::EOF => ErrorKind::UnexpectedEof,
+        found => ErrorKind::UnexpectedToken { expected, found },
+    }
+}
+
+fn exceeds(limit: Option<usize>, value: usize) -> bool {
+    matches!(limit, Some(max) if value > max)
+}
+
 pub struct Parser<'a, T>
 where
     T: Iterator<Item = u8>,
 {
     lexer: Lexer<'a, T>,
+    reader: reader::Reader,
+    sink: BNodeSink,
 }
 
 impl<'a, T> Parser<'a, T>
@@ -262,9 +908,36 @@ where
     T: Iterator<Item = u8>,
 {
     pub fn new(stream: &'a mut T) -> Parser<'a, T> {
+        Parser::with_config(stream, ParserConfig::default())
+    }
+
+    pub fn with_config(stream: &'a mut T, config: ParserConfig) -> Parser<'a, T> {
         Parser {
             lexer: Lexer::new(stream),
+            reader: reader::Reader::new(config),
+            sink: BNodeSink::default(),
+        }
+    }
+
+    fn with_lenient_config(stream: &'a mut T, lenient: LenientConfig) -> Parser<'a, T> {
+        let mut lexer = Lexer::new(stream);
+        if lenient.leading_zeros {
+            lexer.warnings = Some(vec![]);
         }
+
+        Parser {
+            lexer,
+            reader: reader::Reader::with_lenient_config(lenient),
+            sink: BNodeSink::default(),
+        }
+    }
+
+    /// Returns the violations tolerated so far in the order of their position.
+    fn take_warnings(&mut self) -> Vec<Error> {
+        let mut warnings = core::mem::take(&mut self.reader.warnings);
+        warnings.extend(self.lexer.warnings.take().unwrap_or_default());
+        warnings.sort_by_key(|warning| warning.position);
+        warnings
     }
 
     pub fn parse(&mut self) -> Result<BNode>
@@ -272,127 +945,304 @@ where
         T: Iterator<Item = u8>,
     {
         let node = self.parse_node()?;
+        self.sink.stats.bytes_consumed = self.lexer.consumed;
 
         match self.lexer.next_token()? {
             Token::EOF => Ok(node),
-            _ => throw!("Expect EOF", self.lexer.position),
+            found => throw!(unexpected("EOF", found), self.lexer.position()),
         }
     }
 
-    fn parse_node(&mut self) -> Result<BNode>
+    pub fn parse_all(&mut self) -> Result<Vec<BNode>>
     where
         T: Iterator<Item = u8>,
     {
-        match self.lexer.look_ahead()? {
-            Token::IntegerBegin => Ok(self.parse_integer()?.into()),
-            Token::Length(_) => Ok(self.parse_bytes()?.into()),
-            Token::ListBegin => Ok(self.parse_list()?.into()),
-            Token::DictBegin => Ok(self.parse_dict()?.into()),
-            _ => throw!("invalid input", self.lexer.position),
+        let mut nodes = vec![];
+
+        while self.lexer.look_ahead()? != Token::EOF {
+            nodes.push(self.parse_node()?);
         }
+
+        Ok(nodes)
     }
 
-    fn parse_integer(&mut self) -> Result<i64>
+    fn parse_node(&mut self) -> Result<BNode>
     where
         T: Iterator<Item = u8>,
     {
-        let token_begin = self.lexer.next_token()?;
-        debug_assert_eq!(Token::IntegerBegin, token_begin);
-
-        let (value, read) = self.lexer.read_i64_before(0, b'e')?;
-
-        if read < 1 {
-            throw!("Integer cannot be empty", self.lexer.position)
-        }
-
-        assert_eq!(Token::End, self.lexer.next_token()?);
-        Ok(value)
+        reader::read_node(&mut self.reader, &mut self.lexer, &mut self.sink)
     }
 
-    fn parse_bytes(&mut self) -> Result<Vec<u8>>
+    #[cfg(test)]
+    fn parse_integer(&mut self) -> Result<BNode>
     where
         T: Iterator<Item = u8>,
     {
-        let next_token = self.lexer.next_token()?;
-        match next_token {
-            Token::Length(len) => {
-                assert_eq!(Token::Colon, self.lexer.next_token()?);
-                Ok(self.lexer.read_bytes(len as usize)?)
-            }
-            _ => throw!("invalid input", self.lexer.position),
-        }
+        debug_assert_eq!(Token::IntegerBegin, self.lexer.look_ahead()?);
+        self.parse_node()
     }
 
-    fn parse_list(&mut self) -> Result<BList>
+    #[cfg(test)]
+    fn parse_bytes(&mut self) -> Result<Vec<u8>>
     where
         T: Iterator<Item = u8>,
     {
-        let token_begin = self.lexer.next_token()?;
-        debug_assert_eq!(Token::ListBegin, token_begin);
-        let mut list = vec![];
-
-        loop {
-            match self.lexer.look_ahead()? {
-                Token::IntegerBegin => {
-                    list.push(self.parse_integer()?.into());
-                }
-                Token::Length(_) => {
-                    list.push(self.parse_bytes()?.into());
-                }
-                Token::ListBegin => {
-                    list.push(self.parse_list()?.into());
-                }
-                Token::DictBegin => {
-                    list.push(self.parse_dict()?.into());
-                }
-                _ => {
-                    if self.lexer.next_token()? != Token::End {
-                        throw!("invalid list", self.lexer.position);
-                    }
-
-                    return Ok(list);
-                }
-            }
-        }
+        debug_assert!(matches!(self.lexer.look_ahead()?, Token::Length(_)));
+        Ok(self.parse_node()?.into_bytes().unwrap())
     }
 
+    #[cfg(test)]
     fn parse_dict(&mut self) -> Result<BDict>
     where
         T: Iterator<Item = u8>,
     {
-        let token_begin = self.lexer.next_token()?;
-        debug_assert_eq!(Token::DictBegin, token_begin);
-        let mut dict = BDict::new();
-        loop {
-            match self.lexer.look_ahead()? {
-                Token::Length(_) => {
-                    let key = String::from_utf8(self.parse_bytes()?).unwrap();
-                    let value = self.parse_node()?;
-                    dict.insert(key, value);
-                }
-                _ => {
-                    if self.lexer.next_token()? != Token::End {
-                        throw!("invalid dictionary", self.lexer.position)
-                    }
+        debug_assert_eq!(Token::DictBegin, self.lexer.look_ahead()?);
+        Ok(self.parse_node()?.into_dict().unwrap())
+    }
+}
 
-                    return Ok(dict);
-                }
-            }
+pub fn parse<T>(stream: &mut T) -> Result<BNode>
+where
+    T: Iterator<Item = u8>,
+{
+    let mut parser = Parser::new(stream);
+    parser.parse()
+}
+
+pub fn parse_with_config<T>(stream: &mut T, config: &ParserConfig) -> Result<BNode>
+where
+    T: Iterator<Item = u8>,
+{
+    let mut parser = Parser::with_config(stream, config.clone());
+    parser.parse()
+}
+
+/// Like [`parse`], also returning metrics of the parsed value.
+pub fn parse_with_stats<T>(stream: &mut T) -> Result<(BNode, ParseStats)>
+where
+    T: Iterator<Item = u8>,
+{
+    let mut parser = Parser::new(stream);
+    let node = parser.parse()?;
+    Ok((node, parser.sink.stats))
+}
+
+/// Parses a single value, tolerating the violations allowed by the default
+/// [`LenientConfig`]. Returns the node unless a fatal error occurred, along with
+/// the tolerated violations followed by the fatal error if any.
+pub fn parse_lenient<T>(stream: &mut T) -> (Option<BNode>, Vec<Error>)
+where
+    T: Iterator<Item = u8>,
+{
+    parse_lenient_with_config(stream, &LenientConfig::default())
+}
+
+pub fn parse_lenient_with_config<T>(
+    stream: &mut T,
+    config: &LenientConfig,
+) -> (Option<BNode>, Vec<Error>)
+where
+    T: Iterator<Item = u8>,
+{
+    let mut parser = Parser::with_lenient_config(stream, config.clone());
+    let result = parser.parse();
+    let mut errors = parser.take_warnings();
+
+    match result {
+        Ok(node) => (Some(node), errors),
+        Err(e) => {
+            errors.push(e);
+            (None, errors)
         }
     }
 }
 
-pub fn parse<T>(stream: &mut T) -> Result<BNode>
+/// Checks that `input` is a single value in canonical form, i.e. it also has
+/// sorted and unique dictionary keys.
+pub fn validate_canonical(input: &[u8]) -> Result<()> {
+    let config = ParserConfig {
+        strict_dict_keys: true,
+        ..Default::default()
+    };
+
+    parse_with_config(&mut input.iter().copied(), &config).map(|_| ())
+}
+
+pub fn parse_all<T>(stream: &mut T) -> Result<Vec<BNode>>
+where
+    T: Iterator<Item = u8>,
+{
+    let mut parser = Parser::new(stream);
+    parser.parse_all()
+}
+
+/// Like [`parse`], but dictionaries become [`BNode::OrderedDict`] keeping their
+/// keys in the order of the input, e.g. to display them as they are stored.
+#[cfg(feature = "indexmap")]
+pub fn parse_ordered<T>(stream: &mut T) -> Result<BNode>
 where
     T: Iterator<Item = u8>,
 {
     let mut parser = Parser::new(stream);
+    parser.sink.ordered = true;
     parser.parse()
 }
 
+pub fn parse_from_slice(input: &[u8]) -> Result<BNode> {
+    parse(&mut input.iter().copied())
+}
+
+pub fn parse_from_str(s: &str) -> Result<BNode> {
+    parse(&mut s.bytes())
+}
+
+/// Parses the first value of `input`, returning it along with the bytes
+/// following it, e.g. for messages concatenated in a stream.
+pub fn parse_partial(input: &[u8]) -> Result<(BNode, &[u8])> {
+    let mut stream = input.iter().copied();
+    let mut parser = Parser::new(&mut stream);
+    let node = parser.parse_node()?;
+
+    // Nothing is looked ahead once a value is complete, so every byte
+    // requested from the stream belongs to it.
+    let consumed = parser.lexer.consumed as usize;
+    Ok((node, &input[consumed..]))
+}
+
+/// Yields the bytes of a reader until it fails, keeping the error.
+#[cfg(feature = "std")]
+struct ReadBytes<R> {
+    bytes: std::io::Bytes<R>,
+    read: u64,
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> Iterator for ReadBytes<R> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        match self.bytes.next()? {
+            Ok(byte) => {
+                self.read += 1;
+                Some(byte)
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+}
+
+/// Parses a single value from `reader`, which gets buffered. A failed read is
+/// reported as [`ErrorKind::Io`] at the offset of the byte which couldn't be
+/// read.
+#[cfg(feature = "std")]
+pub fn parse_from_read<R: std::io::Read>(reader: R) -> Result<BNode> {
+    let mut bytes = ReadBytes {
+        bytes: std::io::Read::bytes(std::io::BufReader::new(reader)),
+        read: 0,
+        error: None,
+    };
+    let result = parse(&mut bytes);
+
+    match bytes.error {
+        Some(e) => throw!(ErrorKind::Io(e.to_string()), bytes.read),
+        None => result,
+    }
+}
+
+/// Parses consecutive values from `reader`, e.g. messages sent over a
+/// connection, reading it in chunks of 4096 bytes.
+///
+/// Error positions are offsets into the whole stream. The iterator stops
+/// after the first error, and ends cleanly only between two values.
+#[cfg(feature = "std")]
+pub struct BNodeStream<R: std::io::Read> {
+    bytes: ReadBytes<std::io::BufReader<R>>,
+    done: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> BNodeStream<R> {
+    pub fn new(reader: R) -> BNodeStream<R> {
+        BNodeStream {
+            bytes: ReadBytes {
+                bytes: std::io::Read::bytes(std::io::BufReader::with_capacity(4096, reader)),
+                read: 0,
+                error: None,
+            },
+            done: false,
+        }
+    }
+
+    fn next_node(&mut self) -> Option<Result<BNode>> {
+        // Reading the first byte upfront tells a clean end from a truncated
+        // value.
+        let first = self.bytes.next()?;
+        let start = self.bytes.read - 1;
+        let mut stream = core::iter::once(first).chain(&mut self.bytes);
+        let result = Parser::new(&mut stream).parse_node();
+
+        Some(result.map_err(|mut e| {
+            e.position += start;
+            e
+        }))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> Iterator for BNodeStream<R> {
+    type Item = Result<BNode>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.next_node();
+        let bytes = &mut self.bytes;
+        let result = match bytes.error.take() {
+            Some(e) => Some(Err(Error::new(ErrorKind::Io(e.to_string()), bytes.read))),
+            None => result,
+        };
+
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
 #[cfg(test)]
-mod tests {
-    use super::{BNode, Lexer, Parser, Token};
+pub(crate) mod tests {
+    use super::{
+        parse_from_slice, parse_from_str, BDict, BList, BNode, ErrorKind, LenientConfig, Lexer,
+        Parser, ParserConfig, Result, Token,
+    };
+
+    pub(crate) const DEBIAN_TORRENT: &str = r#"d8:announce41:http://bttracker.debian.org:6969/announce7:comment35:"Debian CD from cdimage.debian.org"13:creation datei1573903810e9:httpseedsl145:https://cdimage.debian.org/cdimage/release/10.2.0//srv/cdbuilder.debian.org/dst/deb-cd/weekly-builds/amd64/iso-cd/debian-10.2.0-amd64-netinst.iso145:https://cdimage.debian.org/cdimage/archive/10.2.0//srv/cdbuilder.debian.org/dst/deb-cd/weekly-builds/amd64/iso-cd/debian-10.2.0-amd64-netinst.isoe4:infod6:lengthi351272960e4:name31:debian-10.2.0-amd64-netinst.iso12:piece lengthi262144eee"#;
+
+    proptest::proptest! {
+        #[test]
+        fn prop_round_trip(node in crate::testing::prop_bnode(4)) {
+            let encoded = node.to_bytes();
+            let parsed = parse_from_slice(&encoded).unwrap();
+
+            proptest::prop_assert!(node.depth() <= 4);
+            proptest::prop_assert_eq!(&node, &parsed);
+            proptest::prop_assert_eq!(encoded, parsed.to_bytes());
+        }
+
+        #[test]
+        fn prop_containers(
+            list in crate::testing::prop_bnode_list(2),
+            dict in crate::testing::prop_bnode_dict(2),
+        ) {
+            proptest::prop_assert!(list.is_list() && list.depth() <= 2);
+            proptest::prop_assert!(dict.is_dict() && dict.depth() <= 2);
+            proptest::prop_assert_eq!(&dict, &parse_from_slice(&dict.to_bytes()).unwrap());
+        }
+    }
 
     #[test]
     fn test_lexer_read_i64_before() {
@@ -453,10 +1303,10 @@ mod tests {
         let mut lexer = Lexer::new(&mut bytes);
 
         let _ = lexer.read_bytes(3).unwrap();
-        assert_eq!(2, lexer.position);
+        assert_eq!(2, lexer.position());
 
         let _ = lexer.read_bytes(4).unwrap();
-        assert_eq!(6, lexer.position);
+        assert_eq!(6, lexer.position());
     }
 
     #[test]
@@ -465,10 +1315,10 @@ mod tests {
         let mut lexer = Lexer::new(&mut bytes);
 
         let _ = lexer.look_ahead().unwrap();
-        assert_eq!(0, lexer.position);
+        assert_eq!(0, lexer.position());
 
         let _ = lexer.look_ahead().unwrap();
-        assert_eq!(0, lexer.position);
+        assert_eq!(0, lexer.position());
     }
 
     #[test]
@@ -477,9 +1327,9 @@ mod tests {
         let mut lexer = Lexer::new(&mut bytes);
 
         lexer.read_i64_before(0, b':').unwrap();
-        assert_eq!(0, lexer.position);
+        assert_eq!(0, lexer.position());
         lexer.read_bytes(1).unwrap();
-        assert_eq!(1, lexer.position);
+        assert_eq!(1, lexer.position());
     }
 
     #[test]
@@ -490,6 +1340,16 @@ mod tests {
         assert_eq!(3, parser.parse_integer().unwrap_err().position)
     }
 
+    #[test]
+    fn test_lexer_position_start() {
+        let mut bytes = "".bytes();
+        let lexer = Lexer::new(&mut bytes);
+        assert_eq!(0, lexer.position());
+
+        assert_eq!(0, parse_from_str("").unwrap_err().position);
+        assert_eq!(4, parse_from_str("3:ab").unwrap_err().position);
+    }
+
     #[test]
     fn test_lexer_look_ahead() {
         let mut bytes = "i256e".bytes();
@@ -508,7 +1368,7 @@ mod tests {
             let mut parser = Parser::new(&mut bytes);
 
             let value = parser.parse_integer().unwrap();
-            assert_eq!(expected, value);
+            assert_eq!(BNode::Integer(expected), value);
         }
     }
 
@@ -536,7 +1396,7 @@ mod tests {
 
     #[test]
     fn test_parse_bytes_failed() {
-        let cases = ["5:hello2", "5:halo", "521"];
+        let cases = ["5:hello2", "5:halo", "521", "1-5:spam", "99999999999:spam"];
         for (i, _) in cases.iter().enumerate() {
             let mut bytes = cases[i].bytes();
             let mut parser = Parser::new(&mut bytes);
@@ -546,6 +1406,82 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(not(feature = "bigint"))]
+    fn test_parse_integer_overflow() {
+        let err = parse_from_str("i99999999999999999999e").unwrap_err();
+        assert_eq!(&ErrorKind::IntegerOverflow, err.kind());
+
+        let err = parse_from_str("99999999999999999999:").unwrap_err();
+        assert_eq!(&ErrorKind::IntegerOverflow, err.kind());
+
+        assert_eq!(
+            BNode::Integer(i64::MAX),
+            parse_from_str("i9223372036854775807e").unwrap()
+        );
+        assert_eq!(
+            BNode::Integer(i64::MIN),
+            parse_from_str("i-9223372036854775808e").unwrap()
+        );
+
+        let err = parse_from_str("i9223372036854775808e").unwrap_err();
+        assert_eq!(&ErrorKind::IntegerOverflow, err.kind());
+        assert_eq!(19, err.position);
+        let err = parse_from_str("i-9223372036854775809e").unwrap_err();
+        assert_eq!(&ErrorKind::IntegerOverflow, err.kind());
+        assert_eq!(20, err.position);
+    }
+
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_parse_big_integer() {
+        use num_bigint::BigInt;
+
+        let raw = "i9999999999999999999999999999e";
+        let bnode = parse_from_str(raw).unwrap();
+        let expected: BigInt = "9999999999999999999999999999".parse().unwrap();
+        assert_eq!(BNode::BigInteger(Box::new(expected.clone())), bnode);
+        assert_eq!(Ok(&expected), bnode.as_bigint());
+        assert!(bnode.as_integer().is_err());
+        assert!(bnode.is_integer());
+        assert_eq!(
+            Err("integer out of the i64 range".to_string()),
+            i64::try_from(bnode.clone())
+        );
+        assert_eq!(raw.as_bytes(), bnode.to_bytes());
+        assert_eq!(raw.len(), bnode.encoded_len());
+        assert_eq!(bnode, parse_from_slice(&bnode.to_bytes()).unwrap());
+        assert!(BNode::Integer(i64::MAX) < bnode);
+
+        let bnode = parse_from_str("li-9223372036854775809ei1ee").unwrap();
+        assert_eq!(
+            BNode::BigInteger(Box::new(BigInt::from(i64::MIN) - 1)),
+            bnode[0]
+        );
+        assert_eq!(BNode::Integer(1), bnode[1]);
+
+        assert_eq!(
+            BNode::Integer(i64::MAX),
+            parse_from_str("i9223372036854775807e").unwrap()
+        );
+        assert!(BNode::Integer(1).as_bigint().is_err());
+
+        // Byte string lengths still have to fit.
+        let err = parse_from_str("99999999999999999999:").unwrap_err();
+        assert_eq!(&ErrorKind::IntegerOverflow, err.kind());
+    }
+
+    #[test]
+    fn test_parse_integer_misplaced_minus() {
+        for case in ["i1-2e", "i--1e", "i-e", "1-2:a"] {
+            let err = parse_from_str(case).unwrap_err();
+            match err.kind() {
+                ErrorKind::InvalidToken(b'-') | ErrorKind::EmptyInteger => {}
+                kind => panic!("{} failed with {:?}", case, kind),
+            }
+        }
+    }
+
     #[test]
     fn test_parse_list() {
         let cases = ["li256e7:bencodeli256e7:bencodeee", "l4:spami42ee", "le"];
@@ -627,7 +1563,7 @@ mod tests {
 
     #[test]
     fn test_parse_nested_dict() {
-        let raw = r#"d8:announce41:http://bttracker.debian.org:6969/announce7:comment35:"Debian CD from cdimage.debian.org"13:creation datei1573903810e9:httpseedsl145:https://cdimage.debian.org/cdimage/release/10.2.0//srv/cdbuilder.debian.org/dst/deb-cd/weekly-builds/amd64/iso-cd/debian-10.2.0-amd64-netinst.iso145:https://cdimage.debian.org/cdimage/archive/10.2.0//srv/cdbuilder.debian.org/dst/deb-cd/weekly-builds/amd64/iso-cd/debian-10.2.0-amd64-netinst.isoe4:infod6:lengthi351272960e4:name31:debian-10.2.0-amd64-netinst.iso12:piece lengthi262144eee"#;
+        let raw = DEBIAN_TORRENT;
 
         let mut bytes = raw.bytes();
         let mut parser = Parser::new(&mut bytes);
@@ -638,4 +1574,834 @@ mod tests {
 
         assert_eq!(&raw.as_bytes(), &buf);
     }
+
+    #[test]
+    fn test_parse_from_slice_and_str() {
+        let raw = "d3:bar4:spam3:fooi42ee";
+
+        let from_slice = parse_from_slice(raw.as_bytes()).unwrap();
+        let from_str = parse_from_str(raw).unwrap();
+        assert_eq!(from_slice, from_str);
+
+        let mut buf = vec![];
+        let _ = from_str.serialize(&mut buf);
+        assert_eq!(raw.as_bytes(), &buf);
+    }
+
+    #[test]
+    fn test_parse_from_slice_and_str_failed() {
+        let cases = ["", "i42ei1e", "4:spamx"];
+        for x in &cases {
+            if parse_from_str(x).is_ok() || parse_from_slice(x.as_bytes()).is_ok() {
+                panic!("`{}` should fail", x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_into_boxed_error() {
+        fn parse_boxed(raw: &str) -> Result<BNode, Box<dyn core::error::Error>> {
+            Ok(parse_from_str(raw)?)
+        }
+
+        let err = parse_boxed("i-0e").unwrap_err();
+        assert_eq!(
+            "bencode error at position 2: Negative zero is not permitted",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_bencode_error() {
+        use super::{BencodeError, IoResult};
+        use core::error::Error as _;
+
+        fn reencode(raw: &str, buf: &mut [u8]) -> IoResult<usize> {
+            let bnode = parse_from_str(raw)?;
+            let mut cursor = std::io::Cursor::new(buf);
+            let w = bnode.serialize(&mut cursor)?;
+            std::io::Write::write_all(&mut cursor, b"!")?;
+            Ok(w)
+        }
+
+        assert_eq!(3, reencode("i1e", &mut [0; 4]).unwrap());
+
+        let err = reencode("i-0e", &mut [0; 4]).unwrap_err();
+        assert!(matches!(&err, BencodeError::Parse(e) if *e.kind() == ErrorKind::NegativeZero));
+        assert_eq!("bencode parse error", err.to_string());
+        assert_eq!(
+            "bencode error at position 2: Negative zero is not permitted",
+            err.source().unwrap().to_string()
+        );
+
+        let err = reencode("i1e", &mut [0; 3]).unwrap_err();
+        assert!(matches!(err, BencodeError::Io(_)));
+        assert_eq!("io error", err.to_string());
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let cases = [
+            ("i-0e", ErrorKind::NegativeZero),
+            ("i01e", ErrorKind::LeadingZero),
+            ("ie", ErrorKind::EmptyInteger),
+            ("i1x", ErrorKind::InvalidToken(b'x')),
+            ("5:halo", ErrorKind::UnexpectedEof),
+            ("l4:halo", ErrorKind::UnexpectedEof),
+            ("d1:\u{ff}i1ee", ErrorKind::DictKeyNotUtf8),
+            (
+                "di1ei2ee",
+                ErrorKind::UnexpectedToken {
+                    expected: "dictionary key or `e`",
+                    found: Token::IntegerBegin,
+                },
+            ),
+        ];
+        for (raw, kind) in cases {
+            assert_eq!(&kind, parse_from_str(raw).unwrap_err().kind(), "{}", raw);
+        }
+
+        let strict = ParserConfig {
+            strict_dict_keys: true,
+            ..Default::default()
+        };
+        let mut bytes = "d3:bari1e3:bari2ee".bytes();
+        assert_eq!(
+            &ErrorKind::DuplicateDictKey,
+            super::parse_with_config(&mut bytes, &strict)
+                .unwrap_err()
+                .kind()
+        );
+    }
+
+    #[test]
+    fn test_parse_with_stats() {
+        let (_, stats) = super::parse_with_stats(&mut "i42e".bytes()).unwrap();
+        assert_eq!(
+            super::ParseStats {
+                max_depth: 0,
+                nodes_parsed: 1,
+                bytes_consumed: 4,
+                integers: 1,
+                ..Default::default()
+            },
+            stats
+        );
+
+        let (node, stats) = super::parse_with_stats(&mut DEBIAN_TORRENT.bytes()).unwrap();
+        let mut nodes = 0;
+        node.walk(|_| nodes += 1);
+        assert_eq!(11, nodes);
+        assert_eq!(
+            super::ParseStats {
+                max_depth: 2,
+                nodes_parsed: 11,
+                bytes_consumed: DEBIAN_TORRENT.len() as u64,
+                integers: 3,
+                byte_strings: 5,
+                lists: 1,
+                dicts: 2,
+            },
+            stats
+        );
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        let (node, rest) = super::parse_partial(b"i1ei2e").unwrap();
+        assert_eq!((BNode::Integer(1), &b"i2e"[..]), (node, rest));
+
+        let (node, rest) = super::parse_partial(rest).unwrap();
+        assert_eq!((BNode::Integer(2), &b""[..]), (node, rest));
+
+        let (node, rest) = super::parse_partial(b"d1:ali1eee4:spam").unwrap();
+        assert_eq!("d1:ali1eee", node.to_string());
+        assert_eq!(b"4:spam", rest);
+
+        for x in [&b""[..], b"l4:spam", b"i1"] {
+            assert_eq!(
+                &ErrorKind::UnexpectedEof,
+                super::parse_partial(x).unwrap_err().kind()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_parse_ordered() {
+        let raw = "d4:name1:a6:lengthi1e5:filesld1:zi1e1:ai2eeee";
+        let bnode = super::parse_ordered(&mut raw.bytes()).unwrap();
+
+        let keys = |node: &BNode| match node {
+            BNode::OrderedDict(dict) => dict.keys().cloned().collect::<Vec<_>>(),
+            _ => panic!("not an ordered dictionary"),
+        };
+        assert_eq!(vec!["name", "length", "files"], keys(&bnode));
+        assert_eq!(vec!["z", "a"], keys(&bnode["files"][0]));
+        assert_eq!(BNode::Integer(1), bnode["length"]);
+
+        let sorted = "d5:filesld1:ai2e1:zi1eee6:lengthi1e4:name1:ae";
+        assert_eq!(sorted.as_bytes(), bnode.to_bytes());
+        assert_eq!(sorted.len(), bnode.encoded_len());
+        assert_eq!(parse_from_str(raw).unwrap().to_bytes(), bnode.to_bytes());
+        // Encoding doesn't reorder the dictionary itself.
+        assert_eq!(vec!["name", "length", "files"], keys(&bnode));
+        assert_eq!(
+            r#"{name: "a", length: 1, files: [{z: 1, a: 2}]}"#,
+            bnode.to_compact_string()
+        );
+
+        let dup = super::parse_ordered(&mut "d1:bi1e1:ai2e1:bi3ee".bytes()).unwrap();
+        assert_eq!(vec!["b", "a"], keys(&dup));
+        assert_eq!(BNode::Integer(3), dup["b"]);
+    }
+
+    #[test]
+    fn test_parse_from_read() {
+        let bnode = super::parse_from_read(std::io::Cursor::new(DEBIAN_TORRENT)).unwrap();
+        assert_eq!(parse_from_str(DEBIAN_TORRENT).unwrap(), bnode);
+
+        struct Failing(usize);
+        impl std::io::Read for Failing {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(std::io::Error::other("disconnected"));
+                }
+                self.0 -= 1;
+                buf[0] = b'l';
+                Ok(1)
+            }
+        }
+
+        let err = super::parse_from_read(Failing(3)).unwrap_err();
+        assert_eq!(3, err.position);
+        assert_eq!(&ErrorKind::Io("disconnected".to_string()), err.kind());
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let raw = "d3:bar4:spam3:fooli42eee";
+        let bnode = parse_from_str(raw).unwrap();
+
+        assert_eq!(raw.as_bytes(), &bnode.to_bytes());
+        assert_eq!(raw, bnode.to_string());
+    }
+
+    #[test]
+    fn test_display_non_utf8_bytes() {
+        let bnode = BNode::Bytes(vec![b'a', 0xff, b'b', 0xc3]);
+
+        assert_eq!("4:a%FFb%C3", bnode.to_string());
+    }
+
+    #[test]
+    fn test_display_escapes_percent() {
+        let bnode = BNode::List(vec![BNode::from("%FF"), BNode::Bytes(vec![0xff])]);
+
+        assert_eq!("l3:%25FF1:%FFe", bnode.to_string());
+    }
+
+    #[test]
+    fn test_is_integer() {
+        assert!(BNode::Integer(42).is_integer());
+        assert!(!BNode::Bytes(vec![]).is_integer());
+    }
+
+    #[test]
+    fn test_is_bytes() {
+        assert!(BNode::Bytes(b"spam".to_vec()).is_bytes());
+        assert!(!BNode::Integer(42).is_bytes());
+    }
+
+    #[test]
+    fn test_is_list() {
+        assert!(BNode::List(vec![]).is_list());
+        assert!(!BNode::Dict(BDict::new()).is_list());
+    }
+
+    #[test]
+    fn test_is_dict() {
+        assert!(BNode::Dict(BDict::new()).is_dict());
+        assert!(!BNode::List(vec![]).is_dict());
+    }
+
+    #[test]
+    fn test_as_str() {
+        let bnode = BNode::from("spam");
+        assert_eq!("spam", bnode.as_str().unwrap());
+        assert_eq!("spam".to_string(), bnode.as_string().unwrap());
+
+        assert!(BNode::Bytes(vec![0xff]).as_str().is_err());
+        assert_eq!(
+            "expected bytes, found integer",
+            BNode::Integer(42).as_string().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_as_utf8_lossy() {
+        use alloc::borrow::Cow;
+
+        let valid = BNode::from("spam");
+        match valid.as_utf8_lossy().unwrap() {
+            Cow::Borrowed(s) => assert_eq!("spam", s),
+            Cow::Owned(_) => panic!("valid UTF-8 should be borrowed"),
+        }
+
+        let invalid = BNode::Bytes(b"sp\xffm".to_vec());
+        match invalid.as_utf8_lossy().unwrap() {
+            Cow::Owned(s) => assert_eq!("sp\u{fffd}m", s),
+            Cow::Borrowed(_) => panic!("invalid UTF-8 should be owned"),
+        }
+
+        assert_eq!(
+            "expected bytes, found integer",
+            BNode::Integer(42).as_utf8_lossy().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_into_variants() {
+        assert_eq!(Ok(42), BNode::Integer(42).into_integer());
+        assert_eq!(Ok(b"spam".to_vec()), BNode::from("spam").into_bytes());
+        assert_eq!(Ok(vec![]), BNode::List(vec![]).into_list());
+        assert_eq!(Ok(BDict::new()), BNode::Dict(BDict::new()).into_dict());
+    }
+
+    #[test]
+    fn test_into_variants_mismatch() {
+        assert_eq!(Err(BNode::from("spam")), BNode::from("spam").into_integer());
+        assert_eq!(Err(BNode::Integer(42)), BNode::Integer(42).into_bytes());
+        assert_eq!(
+            Err(BNode::Dict(BDict::new())),
+            BNode::Dict(BDict::new()).into_list()
+        );
+        assert_eq!(Err(BNode::List(vec![])), BNode::List(vec![]).into_dict());
+    }
+
+    #[test]
+    fn test_as_mut_variants() {
+        let mut integer = BNode::Integer(41);
+        *integer.as_integer_mut().unwrap() += 1;
+        assert_eq!(b"i42e".to_vec(), integer.to_bytes());
+
+        let mut bytes = BNode::from("spam");
+        bytes.as_bytes_mut().unwrap().extend_from_slice(b"spam");
+        assert_eq!(b"8:spamspam".to_vec(), bytes.to_bytes());
+
+        let mut list = BNode::List(vec![]);
+        list.as_list_mut().unwrap().push(BNode::Integer(0));
+        assert_eq!(b"li0ee".to_vec(), list.to_bytes());
+
+        let mut dict = BNode::Dict(BDict::new());
+        dict.as_dict_mut()
+            .unwrap()
+            .insert("foo".to_string(), BNode::Integer(42));
+        assert_eq!(b"d3:fooi42ee".to_vec(), dict.to_bytes());
+
+        assert!(integer.as_list_mut().is_err());
+        assert!(list.as_integer_mut().is_err());
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let mut bytes = "i1ei2ei3e".bytes();
+        let nodes = super::parse_all(&mut bytes).unwrap();
+        assert_eq!(
+            vec![BNode::Integer(1), BNode::Integer(2), BNode::Integer(3)],
+            nodes
+        );
+
+        let mut bytes = "".bytes();
+        assert!(super::parse_all(&mut bytes).unwrap().is_empty());
+
+        let mut bytes = "i1e4:spamli2ee".bytes();
+        let mut parser = Parser::new(&mut bytes);
+        assert_eq!(3, parser.parse_all().unwrap().len());
+    }
+
+    #[test]
+    fn test_parse_all_failed() {
+        let cases = ["i1ei2", "i1ee", "4:spam3:ab"];
+        for x in &cases {
+            let mut bytes = x.bytes();
+            if super::parse_all(&mut bytes).is_ok() {
+                panic!("`{}` should fail", x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_with_config_max_depth() {
+        let config = ParserConfig {
+            max_depth: Some(3),
+            ..Default::default()
+        };
+
+        let mut bytes = "llli1eeee".bytes();
+        assert!(super::parse_with_config(&mut bytes, &config).is_ok());
+
+        let mut bytes = "lld3:fooli1eeeee".bytes();
+        let err = super::parse_with_config(&mut bytes, &config).unwrap_err();
+        assert_eq!(8, err.position);
+    }
+
+    #[test]
+    fn test_parse_with_config_limits() {
+        let config = ParserConfig {
+            max_bytes_len: Some(4),
+            max_list_elements: Some(2),
+            max_dict_entries: Some(1),
+            ..Default::default()
+        };
+
+        let cases = ["l4:spami1ee", "d3:fooi1ee"];
+        for x in &cases {
+            let mut bytes = x.bytes();
+            assert!(super::parse_with_config(&mut bytes, &config).is_ok());
+        }
+
+        let cases = ["5:hello", "li1ei2ei3ee", "d3:bari1e3:fooi2ee"];
+        for x in &cases {
+            let mut bytes = x.bytes();
+            if super::parse_with_config(&mut bytes, &config).is_ok() {
+                panic!("`{}` should exceed the limits", x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_deeply_nested_list() {
+        let depth = 5000;
+        let raw = "l".repeat(depth) + &"e".repeat(depth);
+
+        let mut node = super::parse_from_str(&raw).unwrap();
+        for _ in 1..depth {
+            node = node.into_list().unwrap().pop().unwrap();
+        }
+        assert_eq!(BNode::List(vec![]), node);
+    }
+
+    #[test]
+    fn test_len() {
+        assert_eq!(0, BNode::Integer(42).len());
+        assert_eq!(4, BNode::from("spam").len());
+        assert_eq!(2, BNode::List(vec![1.into(), 2.into()]).len());
+
+        let mut dict = BDict::new();
+        dict.insert("foo".to_string(), 42.into());
+        assert_eq!(1, BNode::Dict(dict).len());
+    }
+
+    #[test]
+    fn test_is_empty() {
+        assert!(!BNode::Integer(0).is_empty());
+        assert!(BNode::from("").is_empty());
+        assert!(!BNode::from("spam").is_empty());
+        assert!(BNode::List(vec![]).is_empty());
+        assert!(!BNode::List(vec![0.into()]).is_empty());
+        assert!(BNode::Dict(BDict::new()).is_empty());
+    }
+
+    #[test]
+    fn test_default() {
+        #[derive(Default)]
+        struct Metadata {
+            value: BNode,
+            list: BList,
+            dict: BDict,
+        }
+
+        assert_eq!(BNode::Integer(0), BNode::default());
+        assert_eq!("i0e", BNode::default().to_string());
+
+        let metadata = Metadata::default();
+        assert_eq!(BNode::Integer(0), metadata.value);
+        assert!(metadata.list.is_empty() && metadata.dict.is_empty());
+    }
+
+    #[test]
+    fn test_from_small_integers() {
+        assert_eq!(BNode::Integer(i32::MIN as i64), BNode::from(i32::MIN));
+        assert_eq!(BNode::Integer(u32::MAX as i64), BNode::from(u32::MAX));
+
+        assert_eq!(BNode::Integer(1), BNode::from(true));
+        assert_eq!(BNode::Integer(0), BNode::from(false));
+        assert_eq!(Ok(&1), parse_from_str("i1e").unwrap().as_integer());
+        assert_eq!("i1e", BNode::from(true).to_string());
+    }
+
+    #[test]
+    fn test_from_narrow_integers_round_trip() {
+        let nodes = [
+            BNode::from(-42i8),
+            BNode::from(-42i16),
+            BNode::from(42u8),
+            BNode::from(42u16),
+        ];
+        let expected = [-42, -42, 42, 42];
+
+        for (node, expected) in nodes.iter().zip(expected) {
+            let parsed = parse_from_slice(&node.to_bytes()).unwrap();
+            assert_eq!(Ok(&expected), parsed.as_integer());
+        }
+        assert_eq!(BNode::Integer(65535), BNode::from(u16::MAX));
+    }
+
+    #[test]
+    fn test_try_from_unsigned() {
+        assert_eq!(
+            BNode::Integer(i64::MAX),
+            BNode::try_from(i64::MAX as u64).unwrap()
+        );
+        let err = BNode::try_from(i64::MAX as u64 + 1).unwrap_err();
+        assert_eq!(&ErrorKind::IntegerOverflow, err.kind());
+
+        assert_eq!(BNode::Integer(42), BNode::try_from(42usize).unwrap());
+        assert!(BNode::try_from(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut bnode = parse_from_str("d4:infod4:name4:spame4:listli1ei2eee").unwrap();
+        assert_eq!(BNode::from("spam"), bnode["info"]["name"]);
+        assert_eq!(BNode::Integer(2), bnode["list"][1]);
+
+        bnode["list"][0] = BNode::Integer(42);
+        bnode["info"]["name"] = BNode::from("eggs");
+        assert_eq!(
+            b"d4:infod4:name4:eggse4:listli42ei2eee".to_vec(),
+            bnode.to_bytes()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "key `missing` not found in dict")]
+    fn test_index_missing_key() {
+        let bnode = parse_from_str("d3:fooi42ee").unwrap();
+        let _ = &bnode["missing"];
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index integer with index 0")]
+    fn test_index_wrong_type() {
+        let bnode = BNode::Integer(42);
+        let _ = &bnode[0];
+    }
+
+    #[test]
+    fn test_partial_eq_primitives() {
+        let bnode = parse_from_str(DEBIAN_TORRENT).unwrap();
+        assert_eq!(bnode["info"]["name"], "debian-10.2.0-amd64-netinst.iso");
+        assert_eq!("debian-10.2.0-amd64-netinst.iso", bnode["info"]["name"]);
+        assert_ne!(bnode["info"]["name"], "debian.iso");
+        assert_eq!(bnode["info"]["length"], 351272960);
+        assert_eq!(351272960, bnode["info"]["length"]);
+        assert_ne!(bnode["info"]["length"], 0);
+
+        let spam = BNode::from("spam");
+        assert!(spam == *"spam");
+        assert!(*"spam" == spam);
+        assert!(spam == *b"spam".as_slice() && *b"spam".as_slice() == spam);
+        assert!(spam == b"spam".as_slice() && b"spam".as_slice() == spam);
+        assert!(spam != *b"eggs".as_slice());
+
+        // Variants never compare equal to values of another kind.
+        assert_ne!(BNode::Integer(4), "4");
+        assert_ne!(spam, 4);
+        assert_ne!(BNode::List(vec![]), b"".as_slice());
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::HashSet;
+        use std::hash::{BuildHasher, RandomState};
+
+        let state = RandomState::new();
+        let a = parse_from_str("d3:bar4:spam3:fooli1eee").unwrap();
+        let b = parse_from_str("d3:bar4:spam3:fooli1eee").unwrap();
+        assert_eq!(state.hash_one(&a), state.hash_one(&b));
+
+        let set: HashSet<BNode> = [a, b, BNode::Integer(1), BNode::from("spam")]
+            .into_iter()
+            .collect();
+        assert_eq!(3, set.len());
+        assert!(set.contains(&BNode::Integer(1)));
+    }
+
+    #[test]
+    fn test_ord() {
+        assert!(BNode::Integer(1) < BNode::Integer(2));
+        assert!(BNode::Integer(0) < BNode::Bytes(b"".to_vec()));
+        assert!(BNode::Bytes(b"abc".to_vec()) < BNode::Bytes(b"abd".to_vec()));
+        assert!(BNode::Bytes(b"zzz".to_vec()) < BNode::List(vec![]));
+        assert!(BNode::List(vec![1.into()]) < BNode::List(vec![1.into(), 0.into()]));
+        assert!(BNode::List(vec![]) < BNode::Dict(BDict::new()));
+
+        let mut nodes = vec![
+            BNode::Dict(BDict::new()),
+            BNode::from("spam"),
+            BNode::List(vec![]),
+            BNode::Integer(42),
+            BNode::Integer(-1),
+        ];
+        nodes.sort();
+        assert_eq!(
+            vec![
+                BNode::Integer(-1),
+                BNode::Integer(42),
+                BNode::from("spam"),
+                BNode::List(vec![]),
+                BNode::Dict(BDict::new()),
+            ],
+            nodes
+        );
+    }
+
+    #[test]
+    fn test_from_iterator() {
+        let list = (0..5).map(BNode::Integer).collect::<BNode>();
+        assert_eq!("li0ei1ei2ei3ei4ee", list.to_string());
+
+        let dict = [("b".to_string(), 2.into()), ("a".to_string(), 1.into())]
+            .into_iter()
+            .collect::<BNode>();
+        assert_eq!("d1:ai1e1:bi2ee", dict.to_string());
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut list = BNode::List(vec![0.into()]);
+        list.extend((1..3).map(BNode::Integer));
+        assert_eq!("li0ei1ei2ee", list.to_string());
+
+        let mut dict = BNode::Dict(BDict::new());
+        dict.extend([("a".to_string(), BNode::from("x"))]);
+        assert_eq!("d1:a1:xe", dict.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "not a list")]
+    fn test_extend_not_a_list() {
+        BNode::Dict(BDict::new()).extend([BNode::Integer(1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a dictionary")]
+    fn test_extend_not_a_dictionary() {
+        BNode::List(vec![]).extend([("a".to_string(), BNode::Integer(1))]);
+    }
+
+    #[test]
+    fn test_sum_product() {
+        let sizes = parse_from_str("li100ei-20ei3ee").unwrap();
+        let list = sizes.as_list().unwrap();
+
+        assert_eq!(BNode::Integer(83), list.iter().sum::<BNode>());
+        assert_eq!(BNode::Integer(83), list.iter().cloned().sum::<BNode>());
+        assert_eq!(BNode::Integer(-6000), list.iter().product::<BNode>());
+        assert_eq!(
+            BNode::Integer(-6000),
+            list.iter().cloned().product::<BNode>()
+        );
+
+        let empty: BList = vec![];
+        assert_eq!(BNode::Integer(0), empty.iter().sum::<BNode>());
+        assert_eq!(BNode::Integer(1), empty.into_iter().product::<BNode>());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot sum bytes")]
+    fn test_sum_not_an_integer() {
+        let _ = [BNode::Integer(1), BNode::from("spam")]
+            .into_iter()
+            .sum::<BNode>();
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(BNode::Integer(7), BNode::Integer(3) + BNode::Integer(4));
+        assert_eq!(BNode::Integer(-1), BNode::Integer(3) - BNode::Integer(4));
+        assert_eq!(BNode::Integer(12), BNode::Integer(3) * BNode::Integer(4));
+        assert_eq!(BNode::Integer(-3), -BNode::Integer(3));
+
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+        let pieces = torrent["info"]["length"].clone() * BNode::Integer(2);
+        assert_eq!(BNode::Integer(702545920), pieces);
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        assert_eq!(
+            Some(BNode::Integer(7)),
+            BNode::Integer(3).checked_add(&BNode::Integer(4))
+        );
+        assert!(BNode::Integer(i64::MAX)
+            .checked_add(&BNode::Integer(1))
+            .is_none());
+        assert!(BNode::Integer(i64::MIN)
+            .checked_sub(&BNode::Integer(1))
+            .is_none());
+        assert!(BNode::Integer(i64::MAX)
+            .checked_mul(&BNode::Integer(2))
+            .is_none());
+        assert!(BNode::Integer(1).checked_add(&BNode::from("1")).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "integer overflow in add")]
+    fn test_add_overflow() {
+        let _ = BNode::Integer(i64::MAX) + BNode::Integer(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "integer overflow in negate")]
+    fn test_negate_overflow() {
+        let _ = -BNode::Integer(i64::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "integer overflow in sum")]
+    fn test_sum_overflow() {
+        let _ = [BNode::Integer(i64::MAX), BNode::Integer(1)]
+            .iter()
+            .sum::<BNode>();
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot add bytes")]
+    fn test_add_not_an_integer() {
+        let _ = BNode::Integer(1) + BNode::from("1");
+    }
+
+    #[test]
+    #[cfg(feature = "bigint")]
+    #[should_panic(expected = "cannot add big integer")]
+    fn test_add_big_integer() {
+        let _ = BNode::Integer(1) + parse_from_str("i99999999999999999999e").unwrap();
+    }
+
+    #[test]
+    fn test_from_str() {
+        let bnode: BNode = "d3:fooi1ee".parse().unwrap();
+        assert_eq!(BNode::Integer(1), bnode["foo"]);
+
+        let err = "".parse::<BNode>().unwrap_err();
+        assert_eq!(parse_from_str("").unwrap_err().kind(), err.kind());
+        assert!("i1ei2e".parse::<BNode>().is_err());
+    }
+
+    #[test]
+    fn test_encoded_len() {
+        let mut nodes = vec![
+            BNode::Integer(0),
+            BNode::Integer(9),
+            BNode::Integer(10),
+            BNode::Integer(-10),
+            BNode::Integer(i64::MAX),
+            BNode::Integer(i64::MIN),
+            BNode::from(""),
+            BNode::from("a".repeat(100)),
+            BNode::List(vec![]),
+            BNode::Dict(BDict::new()),
+        ];
+        nodes.push(parse_from_str(DEBIAN_TORRENT).unwrap());
+        nodes.push(BNode::List(nodes.clone()));
+
+        for node in &nodes {
+            assert_eq!(node.to_bytes().len(), node.encoded_len());
+        }
+    }
+
+    #[test]
+    fn test_serialize_deeply_nested() {
+        let depth = 1000;
+
+        let mut list = BNode::List(vec![]);
+        let mut dict = BNode::Dict(BDict::new());
+        for _ in 1..depth {
+            list = BNode::List(vec![list]);
+            let mut outer = BDict::new();
+            outer.insert("a".to_string(), dict);
+            dict = BNode::Dict(outer);
+        }
+
+        let raw = "l".repeat(depth) + &"e".repeat(depth);
+        assert_eq!(raw.as_bytes(), &list.to_bytes());
+
+        let raw = "d1:a".repeat(depth - 1) + "d" + &"e".repeat(depth);
+        assert_eq!(raw.as_bytes(), &dict.to_bytes());
+    }
+
+    #[test]
+    fn test_parse_strict_dict_keys() {
+        let strict = ParserConfig {
+            strict_dict_keys: true,
+            ..Default::default()
+        };
+
+        let mut bytes = "d3:bari1e3:fooi2ee".bytes();
+        assert!(super::parse_with_config(&mut bytes, &strict).is_ok());
+
+        let cases = ["d3:fooi1e3:bari2ee", "d3:bari1e3:bari2ee"];
+        for x in &cases {
+            assert!(parse_from_str(x).is_ok());
+
+            let mut bytes = x.bytes();
+            if super::parse_with_config(&mut bytes, &strict).is_ok() {
+                panic!("`{}` should fail in strict mode", x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let (node, warnings) = super::parse_lenient(&mut "d3:bari1e3:arei2ee".bytes());
+        assert_eq!(Some(parse_from_str("d3:arei2e3:bari1ee").unwrap()), node);
+        assert_eq!(1, warnings.len());
+        assert_eq!(&ErrorKind::DictKeyOutOfOrder, warnings[0].kind());
+
+        let (node, warnings) = super::parse_lenient(&mut "d1:ai1e1:ai2e1:bi007ee".bytes());
+        assert_eq!(Some(parse_from_str("d1:ai2e1:bi7ee").unwrap()), node);
+        let kinds: Vec<_> = warnings.iter().map(|w| w.kind().clone()).collect();
+        assert_eq!(
+            vec![ErrorKind::DuplicateDictKey, ErrorKind::LeadingZero],
+            kinds
+        );
+
+        let (node, warnings) = super::parse_lenient(&mut "li01ei2".bytes());
+        assert_eq!(None, node);
+        assert_eq!(2, warnings.len());
+        assert_eq!(&ErrorKind::UnexpectedEof, warnings[1].kind());
+
+        let (node, warnings) = super::parse_lenient(&mut "i1e".bytes());
+        assert_eq!(Some(BNode::Integer(1)), node);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_lenient_fatal() {
+        let config = LenientConfig {
+            unsorted_dict_keys: false,
+            ..Default::default()
+        };
+
+        let mut bytes = "d3:bari1e3:arei2ee".bytes();
+        let (node, errors) = super::parse_lenient_with_config(&mut bytes, &config);
+        assert_eq!(None, node);
+        assert_eq!(&ErrorKind::DictKeyOutOfOrder, errors[0].kind());
+
+        let mut bytes = "d1:ai1e1:ai2ee".bytes();
+        let (node, errors) = super::parse_lenient_with_config(&mut bytes, &config);
+        assert!(node.is_some());
+        assert_eq!(1, errors.len());
+    }
+
+    #[test]
+    fn test_validate_canonical() {
+        assert!(super::validate_canonical(DEBIAN_TORRENT.as_bytes()).is_ok());
+        assert!(super::validate_canonical(b"ld1:ai1e1:ai2eee").is_err());
+        assert!(super::validate_canonical(b"i01e").is_err());
+    }
 }
@@ -0,0 +1,273 @@
+use crate::prelude::*;
+use crate::{BNode, BNodeType};
+
+impl BNode {
+    pub fn push(&mut self, item: BNode) -> core::result::Result<(), String> {
+        self.as_list_mut()?.push(item);
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> core::result::Result<Option<BNode>, String> {
+        Ok(self.as_list_mut()?.pop())
+    }
+
+    /// Like `Vec::insert`, but fails instead of panicking when `index` is
+    /// greater than the length.
+    pub fn insert_at(&mut self, index: usize, item: BNode) -> core::result::Result<(), String> {
+        let list = self.as_list_mut()?;
+        if index > list.len() {
+            return Err("index out of bounds".into());
+        }
+
+        list.insert(index, item);
+        Ok(())
+    }
+
+    /// Like `Vec::remove`, but fails instead of panicking when `index` is out
+    /// of bounds.
+    pub fn remove_at(&mut self, index: usize) -> core::result::Result<BNode, String> {
+        let list = self.as_list_mut()?;
+        if index >= list.len() {
+            return Err("index out of bounds".into());
+        }
+
+        Ok(list.remove(index))
+    }
+
+    /// Keeps only the list elements for which `f` returns `true`.
+    pub fn retain<F>(&mut self, f: F) -> core::result::Result<(), String>
+    where
+        F: FnMut(&BNode) -> bool,
+    {
+        self.as_list_mut()?.retain(f);
+        Ok(())
+    }
+
+    pub fn sort_list_by<F>(&mut self, compare: F) -> core::result::Result<(), String>
+    where
+        F: FnMut(&BNode, &BNode) -> core::cmp::Ordering,
+    {
+        self.as_list_mut()?.sort_by(compare);
+        Ok(())
+    }
+
+    /// Sorts the list elements by their [`Ord`] implementation.
+    pub fn sort_list(&mut self) -> core::result::Result<(), String> {
+        self.sort_list_by(BNode::cmp)
+    }
+
+    /// Removes consecutive elements `same` considers equal, keeping the first.
+    pub fn dedup_list_by<F>(&mut self, same: F) -> core::result::Result<(), String>
+    where
+        F: FnMut(&mut BNode, &mut BNode) -> bool,
+    {
+        self.as_list_mut()?.dedup_by(same);
+        Ok(())
+    }
+
+    /// Expands nested lists recursively into a single-level list, any other
+    /// element is kept as is.
+    pub fn flatten_list(self) -> core::result::Result<BNode, String> {
+        let list = self
+            .into_list()
+            .map_err(|node| node.type_mismatch(BNodeType::List))?;
+        let mut flat = vec![];
+        let mut stack = vec![list.into_iter()];
+
+        while let Some(iter) = stack.last_mut() {
+            match iter.next() {
+                Some(BNode::List(list)) => stack.push(list.into_iter()),
+                Some(node) => flat.push(node),
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+
+        Ok(flat.into())
+    }
+
+    /// Expands the lists directly contained in the list by one level.
+    pub fn flatten_list_one(self) -> core::result::Result<BNode, String> {
+        let list = self
+            .into_list()
+            .map_err(|node| node.type_mismatch(BNodeType::List))?;
+        let mut flat = vec![];
+
+        for node in list {
+            match node {
+                BNode::List(list) => flat.extend(list),
+                node => flat.push(node),
+            }
+        }
+
+        Ok(flat.into())
+    }
+
+    /// Pairs up the elements of two lists of the same length into a list of
+    /// two-element lists.
+    pub fn list_zip(self, other: BNode) -> core::result::Result<BNode, String> {
+        let list = self
+            .into_list()
+            .map_err(|node| node.type_mismatch(BNodeType::List))?;
+        let other = other
+            .into_list()
+            .map_err(|node| node.type_mismatch(BNodeType::List))?;
+        if list.len() != other.len() {
+            return Err("lists have different lengths".into());
+        }
+
+        Ok(list
+            .into_iter()
+            .zip(other)
+            .map(|(a, b)| BNode::List(vec![a, b]))
+            .collect())
+    }
+
+    /// The reverse of [`BNode::list_zip`], every element must be a list of
+    /// two elements.
+    pub fn list_unzip(self) -> core::result::Result<(BNode, BNode), String> {
+        let list = self
+            .into_list()
+            .map_err(|node| node.type_mismatch(BNodeType::List))?;
+        let mut firsts = Vec::with_capacity(list.len());
+        let mut seconds = Vec::with_capacity(list.len());
+
+        for pair in list {
+            match pair {
+                BNode::List(pair) if pair.len() == 2 => {
+                    let mut pair = pair.into_iter();
+                    firsts.push(pair.next().unwrap());
+                    seconds.push(pair.next().unwrap());
+                }
+                _ => return Err("not a list of pairs".into()),
+            }
+        }
+
+        Ok((firsts.into(), seconds.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_from_str, BDict, BNode};
+
+    #[test]
+    fn test_push_pop() {
+        let mut bnode = BNode::List(vec![]);
+
+        bnode.push(1.into()).unwrap();
+        assert_eq!("li1ee", bnode.to_string());
+        assert_eq!(Ok(Some(BNode::Integer(1))), bnode.pop());
+        assert_eq!(Ok(None), bnode.pop());
+    }
+
+    #[test]
+    fn test_insert_remove_at() {
+        let mut bnode = BNode::List(vec![2.into()]);
+
+        bnode.insert_at(0, 1.into()).unwrap();
+        bnode.insert_at(2, 3.into()).unwrap();
+        assert_eq!("li1ei2ei3ee", bnode.to_string());
+        assert_eq!(
+            Err("index out of bounds".to_string()),
+            bnode.insert_at(4, 4.into())
+        );
+
+        assert_eq!(Ok(BNode::Integer(3)), bnode.remove_at(2));
+        assert_eq!(Err("index out of bounds".to_string()), bnode.remove_at(2));
+        assert_eq!("li1ei2ee", bnode.to_string());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut bnode = parse_from_str("li1e4:spami2e4:eggse").unwrap();
+
+        bnode.retain(|node| !node.is_integer()).unwrap();
+        assert_eq!("l4:spam4:eggse", bnode.to_string());
+    }
+
+    #[test]
+    fn test_sort_list() {
+        let mut bnode = parse_from_str("l4:spam3:egg5:bacone").unwrap();
+
+        bnode.sort_list().unwrap();
+        assert_eq!("l5:bacon3:egg4:spame", bnode.to_string());
+
+        bnode.sort_list_by(|a, b| a.len().cmp(&b.len())).unwrap();
+        assert_eq!("l3:egg4:spam5:bacone", bnode.to_string());
+    }
+
+    #[test]
+    fn test_dedup_list_by() {
+        let mut bnode = parse_from_str("li1ei1e4:spam4:spami1ee").unwrap();
+
+        bnode.dedup_list_by(|a, b| a == b).unwrap();
+        assert_eq!("li1e4:spami1ee", bnode.to_string());
+    }
+
+    #[test]
+    fn test_flatten_list() {
+        let bnode = parse_from_str("lli1ei2eeli3eli4ei5eeed1:ali6eeee").unwrap();
+
+        assert_eq!(
+            Ok("li1ei2ei3eli4ei5eed1:ali6eeee".to_string()),
+            bnode.clone().flatten_list_one().map(|n| n.to_string())
+        );
+        assert_eq!(
+            Ok("li1ei2ei3ei4ei5ed1:ali6eeee".to_string()),
+            bnode.flatten_list().map(|n| n.to_string())
+        );
+        assert_eq!(
+            Ok(BNode::List(vec![])),
+            parse_from_str("llleleee").unwrap().flatten_list()
+        );
+    }
+
+    #[test]
+    fn test_list_zip() {
+        let numbers = parse_from_str("li1ei2ee").unwrap();
+        let letters = parse_from_str("l1:a1:be").unwrap();
+
+        let zipped = numbers.clone().list_zip(letters.clone()).unwrap();
+        assert_eq!("lli1e1:aeli2e1:bee", zipped.to_string());
+        assert_eq!(Ok((numbers.clone(), letters)), zipped.list_unzip());
+
+        assert_eq!(
+            Err("lists have different lengths".to_string()),
+            numbers.clone().list_zip(BNode::List(vec![]))
+        );
+        assert_eq!(
+            Err("expected list, found integer".to_string()),
+            numbers.clone().list_zip(BNode::Integer(1))
+        );
+        assert_eq!(Err("not a list of pairs".to_string()), numbers.list_unzip());
+        assert_eq!(
+            Ok((BNode::List(vec![]), BNode::List(vec![]))),
+            BNode::List(vec![]).list_unzip()
+        );
+    }
+
+    #[test]
+    fn test_list_methods_not_a_list() {
+        let nodes = [
+            BNode::Integer(1),
+            BNode::from("spam"),
+            BNode::Dict(BDict::new()),
+        ];
+        for mut node in nodes {
+            let err = format!("expected list, found {}", node.type_name());
+            assert_eq!(Err(err.clone()), node.push(1.into()));
+            assert_eq!(Err(err.clone()), node.pop());
+            assert_eq!(Err(err.clone()), node.insert_at(0, 1.into()));
+            assert_eq!(Err(err.clone()), node.remove_at(0));
+            assert_eq!(Err(err.clone()), node.retain(|_| true));
+            assert_eq!(Err(err.clone()), node.sort_list());
+            assert_eq!(Err(err.clone()), node.dedup_list_by(|a, b| a == b));
+            assert_eq!(Err(err.clone()), node.clone().flatten_list());
+            assert_eq!(Err(err.clone()), node.clone().list_zip(BNode::List(vec![])));
+            assert_eq!(Err(err.clone()), node.clone().list_unzip());
+            assert_eq!(Err(err), node.flatten_list_one());
+        }
+    }
+}
@@ -0,0 +1,33 @@
+/// Constructs a [`BNode`](crate::BNode) inline.
+///
+/// Dictionaries are written as `{ key => value, ... }` and lists as
+/// `[value, ...]`, every value is converted with `BNode::from`, so string
+/// literals become `BNode::Bytes` and integers become `BNode::Integer`.
+///
+/// ```
+/// use bencodex::bnode;
+///
+/// let torrent = bnode!({
+///     "announce" => "http://tracker.example.com/announce",
+///     "info" => bnode!({ "name" => "file.iso", "length" => 1024i64 }),
+///     "tags" => bnode!(["linux", "iso"]),
+/// });
+/// assert_eq!(torrent["info"]["length"], bnode!(1024i64));
+/// ```
+#[macro_export]
+macro_rules! bnode {
+    ({ $($key:expr => $value:expr),* $(,)? }) => {{
+        #[allow(unused_mut)]
+        let mut dict = $crate::BDict::new();
+        $(
+            dict.insert(::core::convert::Into::into($key), $crate::BNode::from($value));
+        )*
+        $crate::BNode::Dict(dict)
+    }};
+    ([ $($value:expr),* $(,)? ]) => {
+        $crate::BNode::List(<$crate::BList>::from([$($crate::BNode::from($value)),*]))
+    };
+    ($value:expr) => {
+        $crate::BNode::from($value)
+    };
+}
@@ -0,0 +1,118 @@
+use crate::prelude::*;
+use crate::BNode;
+use core::fmt::Display;
+use core::str::FromStr;
+
+/// The kind of a node, without its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BNodeType {
+    Integer,
+    Bytes,
+    List,
+    Dict,
+}
+
+impl BNodeType {
+    /// Returns `"integer"`, `"bytes"`, `"list"` or `"dict"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BNodeType::Integer => "integer",
+            BNodeType::Bytes => "bytes",
+            BNodeType::List => "list",
+            BNodeType::Dict => "dict",
+        }
+    }
+}
+
+impl Display for BNodeType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The reverse of `Display`, names are matched exactly.
+impl FromStr for BNodeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "integer" => Ok(BNodeType::Integer),
+            "bytes" => Ok(BNodeType::Bytes),
+            "list" => Ok(BNodeType::List),
+            "dict" => Ok(BNodeType::Dict),
+            _ => Err(format!("unknown node type `{}`", s)),
+        }
+    }
+}
+
+impl BNode {
+    /// Big integers are integers and ordered dictionaries are dictionaries.
+    pub fn node_type(&self) -> BNodeType {
+        match self {
+            BNode::Integer(_) => BNodeType::Integer,
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(_) => BNodeType::Integer,
+            BNode::Bytes(_) => BNodeType::Bytes,
+            BNode::List(_) => BNodeType::List,
+            BNode::Dict(_) => BNodeType::Dict,
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(_) => BNodeType::Dict,
+        }
+    }
+
+    /// Returns `"integer"`, `"bytes"`, `"list"` or `"dict"`, e.g. for error
+    /// messages.
+    pub fn type_name(&self) -> &'static str {
+        self.node_type().as_str()
+    }
+
+    /// The error of an `as_*` method called on the wrong kind of node.
+    pub(crate) fn type_mismatch(&self, expected: BNodeType) -> String {
+        format!("expected {}, found {}", expected, self.type_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BNodeType;
+    use crate::{parse_from_str, BNode};
+
+    #[test]
+    fn test_node_type() {
+        let bnode = parse_from_str("d1:ai1e1:b4:spam1:cle1:ddee").unwrap();
+
+        assert_eq!(BNodeType::Dict, bnode.node_type());
+        assert_eq!(BNodeType::Integer, bnode["a"].node_type());
+        assert_eq!("bytes", bnode["b"].type_name());
+        assert_eq!("list", bnode["c"].type_name());
+        assert_eq!("dict", bnode["d"].type_name());
+    }
+
+    #[test]
+    fn test_node_type_from_str() {
+        for ty in [
+            BNodeType::Integer,
+            BNodeType::Bytes,
+            BNodeType::List,
+            BNodeType::Dict,
+        ] {
+            assert_eq!(Ok(ty), ty.to_string().parse());
+        }
+        assert_eq!(
+            Err("unknown node type `Dict`".to_string()),
+            "Dict".parse::<BNodeType>()
+        );
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        assert_eq!(
+            Err("expected integer, found bytes".to_string()),
+            BNode::from("spam").as_integer()
+        );
+        assert_eq!(
+            Err("expected dict, found list".to_string()),
+            BNode::List(vec![]).as_dict()
+        );
+    }
+}
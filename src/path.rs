@@ -0,0 +1,451 @@
+use crate::prelude::*;
+use crate::{BDict, BNode};
+
+/// One level of a path into nested nodes.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum PathStep {
+    /// Looks up a key in a dictionary.
+    Key(String),
+    /// Looks up an index in a list.
+    Index(usize),
+}
+
+impl PathStep {
+    /// Splits a slash-separated path, numeric segments become list indices.
+    fn parse_all(path: &str) -> Vec<PathStep> {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment.parse() {
+                Ok(index) => PathStep::Index(index),
+                Err(_) => PathStep::Key(segment.to_string()),
+            })
+            .collect()
+    }
+}
+
+impl BNode {
+    fn step(&self, step: &PathStep) -> Option<&BNode> {
+        match (self, step) {
+            (node, PathStep::Key(key)) => node.get(key),
+            (BNode::List(list), PathStep::Index(index)) => list.get(*index),
+            _ => None,
+        }
+    }
+
+    fn step_mut(&mut self, step: &PathStep) -> Option<&mut BNode> {
+        match (self, step) {
+            (node, PathStep::Key(key)) => node.get_mut(key),
+            (BNode::List(list), PathStep::Index(index)) => list.get_mut(*index),
+            _ => None,
+        }
+    }
+
+    /// Follows `steps` from this node, `None` if any step does not exist.
+    pub fn get_path<'a>(&'a self, steps: &[PathStep]) -> Option<&'a BNode> {
+        steps.iter().try_fold(self, |node, step| node.step(step))
+    }
+
+    pub fn get_path_mut<'a>(&'a mut self, steps: &[PathStep]) -> Option<&'a mut BNode> {
+        steps
+            .iter()
+            .try_fold(self, |node, step| node.step_mut(step))
+    }
+
+    /// Replaces the node at `path`, missing dictionaries along the way are
+    /// created. An empty path replaces `self`.
+    pub fn set_path(&mut self, path: &[PathStep], value: BNode) -> Result<(), String> {
+        let Some((last, parents)) = path.split_last() else {
+            *self = value;
+            return Ok(());
+        };
+
+        let mut node = self;
+        for step in parents {
+            node = match step {
+                PathStep::Key(key) => {
+                    node.get_or_insert_default(key.clone(), BDict::new().into())?
+                }
+                PathStep::Index(index) => node
+                    .as_list_mut()?
+                    .get_mut(*index)
+                    .ok_or("index out of bounds")?,
+            };
+        }
+
+        match last {
+            PathStep::Key(key) => {
+                node.insert_entry(key.clone(), value)?;
+            }
+            PathStep::Index(index) => {
+                *node
+                    .as_list_mut()?
+                    .get_mut(*index)
+                    .ok_or("index out of bounds")? = value;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the node at `path`, `None` if it doesn't exist. The root can't
+    /// be removed.
+    pub fn delete_path(&mut self, path: &[PathStep]) -> Option<BNode> {
+        let (last, parents) = path.split_last()?;
+
+        match (self.get_path_mut(parents)?, last) {
+            (node, PathStep::Key(key)) => node.remove_entry(key).ok().flatten(),
+            (BNode::List(list), PathStep::Index(index)) if *index < list.len() => {
+                Some(list.remove(*index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the path of every integer and byte string in depth-first
+    /// order, empty containers have no paths.
+    pub fn all_paths(&self) -> Vec<Vec<PathStep>> {
+        self.all_paths_with_values()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Like [`BNode::all_paths`], paired with the node at each path.
+    pub fn all_paths_with_values(&self) -> Vec<(Vec<PathStep>, &BNode)> {
+        self.leaves_with_path().collect()
+    }
+
+    /// Like [`BNode::leaves`], paired with the path of each leaf.
+    pub fn leaves_with_path(&self) -> impl Iterator<Item = (Vec<PathStep>, &BNode)> + '_ {
+        let mut stack = vec![(vec![], self)];
+
+        core::iter::from_fn(move || {
+            while let Some((path, node)) = stack.pop() {
+                match node {
+                    BNode::List(list) => {
+                        stack.extend(list.iter().enumerate().rev().map(|(i, node)| {
+                            let mut path = path.clone();
+                            path.push(PathStep::Index(i));
+                            (path, node)
+                        }));
+                    }
+                    BNode::Dict(dict) => {
+                        stack.extend(dict.iter().rev().map(|(key, node)| {
+                            let mut path = path.clone();
+                            path.push(PathStep::Key(key.clone()));
+                            (path, node)
+                        }));
+                    }
+                    #[cfg(feature = "indexmap")]
+                    BNode::OrderedDict(dict) => {
+                        stack.extend(dict.iter().rev().map(|(key, node)| {
+                            let mut path = path.clone();
+                            path.push(PathStep::Key(key.clone()));
+                            (path, node)
+                        }));
+                    }
+                    _ => return Some((path, node)),
+                }
+            }
+
+            None
+        })
+    }
+
+    /// Like [`BNode::walk`], the visitor also receives the path from this node
+    /// to the visited one.
+    pub fn walk_with_path<F>(&self, mut visitor: F)
+    where
+        F: FnMut(&[PathStep], &BNode),
+    {
+        let mut stack: Vec<(Vec<PathStep>, &BNode)> = vec![(vec![], self)];
+
+        while let Some((path, node)) = stack.pop() {
+            visitor(&path, node);
+
+            match node {
+                BNode::List(list) => {
+                    stack.extend(list.iter().enumerate().rev().map(|(i, node)| {
+                        let mut path = path.clone();
+                        path.push(PathStep::Index(i));
+                        (path, node)
+                    }));
+                }
+                BNode::Dict(dict) => {
+                    stack.extend(dict.iter().rev().map(|(key, node)| {
+                        let mut path = path.clone();
+                        path.push(PathStep::Key(key.clone()));
+                        (path, node)
+                    }));
+                }
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => {
+                    stack.extend(dict.iter().rev().map(|(key, node)| {
+                        let mut path = path.clone();
+                        path.push(PathStep::Key(key.clone()));
+                        (path, node)
+                    }));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Like [`BNode::walk_mut`], the visitor also receives the path from this
+    /// node to the visited one.
+    pub fn walk_with_path_mut<F>(&mut self, mut visitor: F)
+    where
+        F: FnMut(&[PathStep], &mut BNode),
+    {
+        let mut stack: Vec<(Vec<PathStep>, &mut BNode)> = vec![(vec![], self)];
+
+        while let Some((path, node)) = stack.pop() {
+            visitor(&path, node);
+
+            match node {
+                BNode::List(list) => {
+                    stack.extend(list.iter_mut().enumerate().rev().map(|(i, node)| {
+                        let mut path = path.clone();
+                        path.push(PathStep::Index(i));
+                        (path, node)
+                    }));
+                }
+                BNode::Dict(dict) => {
+                    stack.extend(dict.iter_mut().rev().map(|(key, node)| {
+                        let mut path = path.clone();
+                        path.push(PathStep::Key(key.clone()));
+                        (path, node)
+                    }));
+                }
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => {
+                    stack.extend(dict.iter_mut().rev().map(|(key, node)| {
+                        let mut path = path.clone();
+                        path.push(PathStep::Key(key.clone()));
+                        (path, node)
+                    }));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Follows a slash-separated path such as `"info/files/0/length"`,
+    /// numeric segments are treated as list indices.
+    pub fn get_path_str(&self, path: &str) -> Option<&BNode> {
+        self.get_path(&PathStep::parse_all(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathStep;
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{parse_from_str, BNode};
+
+    #[test]
+    fn test_get_path() {
+        let bnode = parse_from_str(DEBIAN_TORRENT).unwrap();
+
+        let steps = [PathStep::Key("info".into()), PathStep::Key("name".into())];
+        assert_eq!(
+            Some(&BNode::from("debian-10.2.0-amd64-netinst.iso")),
+            bnode.get_path(&steps)
+        );
+        assert_eq!(bnode.get_path(&steps), bnode.get_path_str("info/name"));
+        assert_eq!(Some(&bnode), bnode.get_path(&[]));
+    }
+
+    #[test]
+    fn test_get_path_index() {
+        let bnode = parse_from_str(DEBIAN_TORRENT).unwrap();
+
+        let seed = bnode.get_path_str("httpseeds/1").unwrap();
+        assert!(seed.as_str().unwrap().contains("/archive/"));
+        assert_eq!(None, bnode.get_path_str("httpseeds/2"));
+        assert_eq!(None, bnode.get_path_str("info/name/0"));
+        assert_eq!(None, bnode.get_path(&[PathStep::Index(0)]));
+    }
+
+    #[test]
+    fn test_get_path_mut() {
+        let mut bnode = parse_from_str("d4:infod6:lengthi1eee").unwrap();
+
+        let steps = [PathStep::Key("info".into()), PathStep::Key("length".into())];
+        *bnode.get_path_mut(&steps).unwrap() = BNode::Integer(42);
+        assert_eq!("d4:infod6:lengthi42eee", bnode.to_string());
+    }
+
+    #[test]
+    fn test_all_paths() {
+        let list = parse_from_str("li1ei2ei3ee").unwrap();
+        assert_eq!(
+            vec![
+                vec![PathStep::Index(0)],
+                vec![PathStep::Index(1)],
+                vec![PathStep::Index(2)]
+            ],
+            list.all_paths()
+        );
+
+        let bnode = parse_from_str("d1:ali1eli2eee1:bd1:c1:xe1:dlee").unwrap();
+        let key = |k: &str| PathStep::Key(k.into());
+        assert_eq!(
+            vec![
+                (vec![key("a"), PathStep::Index(0)], &BNode::Integer(1)),
+                (
+                    vec![key("a"), PathStep::Index(1), PathStep::Index(0)],
+                    &BNode::Integer(2)
+                ),
+                (vec![key("b"), key("c")], &BNode::from("x")),
+            ],
+            bnode.all_paths_with_values()
+        );
+        for (path, node) in bnode.all_paths_with_values() {
+            assert_eq!(Some(node), bnode.get_path(&path));
+        }
+
+        assert_eq!(vec![Vec::<PathStep>::new()], BNode::Integer(1).all_paths());
+    }
+
+    #[test]
+    fn test_set_path() {
+        let mut bnode = parse_from_str("d4:infod4:name1:aee").unwrap();
+        let key = |k: &str| PathStep::Key(k.into());
+
+        bnode
+            .set_path(&[key("info"), key("name")], "b".into())
+            .unwrap();
+        bnode
+            .set_path(&[key("x"), key("y")], BNode::List(vec![0.into()]))
+            .unwrap();
+        bnode
+            .set_path(&[key("x"), key("y"), PathStep::Index(0)], 1.into())
+            .unwrap();
+        assert_eq!("d4:infod4:name1:be1:xd1:yli1eeee", bnode.to_string());
+
+        assert_eq!(
+            Err("index out of bounds".to_string()),
+            bnode.set_path(&[key("x"), key("y"), PathStep::Index(1)], 1.into())
+        );
+        assert_eq!(
+            Err("expected list, found dict".to_string()),
+            bnode.set_path(&[key("info"), PathStep::Index(0)], 1.into())
+        );
+        assert_eq!(
+            Err("expected dict, found bytes".to_string()),
+            bnode.set_path(&[key("info"), key("name"), key("z")], 1.into())
+        );
+
+        bnode.set_path(&[], 1.into()).unwrap();
+        assert_eq!(BNode::Integer(1), bnode);
+    }
+
+    #[test]
+    fn test_delete_path() {
+        let mut bnode = parse_from_str("d1:ali1ei2ee1:bi3ee").unwrap();
+        let key = |k: &str| PathStep::Key(k.into());
+
+        assert_eq!(
+            Some(BNode::Integer(1)),
+            bnode.delete_path(&[key("a"), PathStep::Index(0)])
+        );
+        assert_eq!(Some(BNode::Integer(3)), bnode.delete_path(&[key("b")]));
+        assert_eq!("d1:ali2eee", bnode.to_string());
+
+        assert_eq!(None, bnode.delete_path(&[key("b")]));
+        assert_eq!(None, bnode.delete_path(&[key("a"), PathStep::Index(1)]));
+        assert_eq!(None, bnode.delete_path(&[key("a"), key("x")]));
+        assert_eq!(None, bnode.delete_path(&[]));
+    }
+
+    #[test]
+    fn test_walk_with_path() {
+        let bnode = parse_from_str("d1:ali1eli2eee1:bd1:c1:xee").unwrap();
+        let key = |k: &str| PathStep::Key(k.into());
+
+        let mut visited = vec![];
+        bnode.walk_with_path(|path, node| visited.push((path.to_vec(), node.to_string())));
+        assert_eq!(
+            vec![
+                (vec![], "d1:ali1eli2eee1:bd1:c1:xee".to_string()),
+                (vec![key("a")], "li1eli2eee".to_string()),
+                (vec![key("a"), PathStep::Index(0)], "i1e".to_string()),
+                (vec![key("a"), PathStep::Index(1)], "li2ee".to_string()),
+                (
+                    vec![key("a"), PathStep::Index(1), PathStep::Index(0)],
+                    "i2e".to_string()
+                ),
+                (vec![key("b")], "d1:c1:xe".to_string()),
+                (vec![key("b"), key("c")], "1:x".to_string()),
+            ],
+            visited
+        );
+        bnode.walk_with_path(|path, node| assert_eq!(Some(node), bnode.get_path(path)));
+    }
+
+    #[test]
+    fn test_walk_with_path_mut() {
+        let mut bnode = parse_from_str("d1:ali1ei2ee1:bi3ee").unwrap();
+
+        bnode.walk_with_path_mut(|path, node| {
+            if let (Some(PathStep::Index(i)), BNode::Integer(n)) = (path.last(), node) {
+                *n += *i as i64 * 10;
+            }
+        });
+        assert_eq!("d1:ali1ei12ee1:bi3ee", bnode.to_string());
+    }
+
+    #[test]
+    fn test_leaves_with_path() {
+        let bnode = parse_from_str("d1:ali1ee1:bde1:c1:xe").unwrap();
+
+        let mut leaves = bnode.leaves_with_path();
+        assert_eq!(
+            Some((
+                vec![PathStep::Key("a".into()), PathStep::Index(0)],
+                &BNode::Integer(1)
+            )),
+            leaves.next()
+        );
+        assert_eq!(
+            Some((vec![PathStep::Key("c".into())], &BNode::from("x"))),
+            leaves.next()
+        );
+        assert_eq!(None, leaves.next());
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_ordered_dict_paths() {
+        let raw = "d1:bd1:c1:xe1:ali1eee";
+        let mut bnode = crate::parse_ordered(&mut raw.bytes()).unwrap();
+        let key = |k: &str| PathStep::Key(k.into());
+
+        assert_eq!(Some(&BNode::from("x")), bnode.get_path_str("b/c"));
+        assert_eq!(
+            vec![vec![key("b"), key("c")], vec![key("a"), PathStep::Index(0)]],
+            bnode.all_paths()
+        );
+
+        let mut visited = vec![];
+        bnode.walk_with_path(|path, _| visited.push(path.to_vec()));
+        assert_eq!(5, visited.len());
+        assert_eq!(vec![key("b"), key("c")], visited[2]);
+
+        bnode.walk_with_path_mut(|path, node| {
+            if path == [key("a"), PathStep::Index(0)] {
+                *node = 2.into();
+            }
+        });
+        bnode.set_path(&[key("b"), key("d")], 3.into()).unwrap();
+        *bnode.get_path_mut(&[key("b"), key("c")]).unwrap() = "y".into();
+        assert_eq!("d1:ali2ee1:bd1:c1:y1:di3eee", bnode.to_string());
+
+        assert_eq!(
+            Some(BNode::from("y")),
+            bnode.delete_path(&[key("b"), key("c")])
+        );
+        assert_eq!(None, bnode.get_path_str("b/c"));
+    }
+}
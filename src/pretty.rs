@@ -0,0 +1,432 @@
+use crate::prelude::*;
+use crate::BNode;
+use core::fmt::{Debug, Display, Formatter, Write};
+
+/// Elements of a list shown by `Debug` before the rest is summarized.
+const DEBUG_MAX_LIST_ITEMS: usize = 10;
+/// Entries of a dictionary shown by `Debug` before the rest is summarized.
+const DEBUG_MAX_DICT_ENTRIES: usize = 5;
+/// Containers nested deeper than this are summarized by `Debug`.
+const DEBUG_MAX_DEPTH: usize = 5;
+/// Bytes of a non-UTF-8 byte string shown in hex by `Debug` before the rest is
+/// elided.
+const DEBUG_MAX_HEX_BYTES: usize = 32;
+
+/// Displays a node as an indented tree, e.g.
+///
+/// ```text
+/// Dict {
+///   "info": Dict {
+///     "length": Integer(351272960)
+///   }
+/// }
+/// ```
+///
+/// Byte strings which are not valid UTF-8 are shown as their length and
+/// content in hex, e.g. `Bytes(<2 bytes: 0xAB0C>)`.
+pub struct PrettyDisplay<'a>(pub &'a BNode);
+
+enum PrettyWork<'a> {
+    Node {
+        node: &'a BNode,
+        key: Option<&'a str>,
+        indent: usize,
+        last: bool,
+    },
+    Close {
+        bracket: char,
+        indent: usize,
+        last: bool,
+    },
+}
+
+/// Separates an element from the next one, the root is not followed by
+/// anything.
+fn end_line(f: &mut Formatter<'_>, indent: usize, last: bool) -> core::fmt::Result {
+    match (indent, last) {
+        (0, _) => Ok(()),
+        (_, true) => f.write_char('\n'),
+        (_, false) => f.write_str(",\n"),
+    }
+}
+
+/// Writes the opening of a non-empty dictionary and schedules its entries,
+/// in the order of `entries`, and its closing bracket.
+fn open_dict<'a, I>(
+    f: &mut Formatter<'_>,
+    stack: &mut Vec<PrettyWork<'a>>,
+    entries: I,
+    indent: usize,
+    last: bool,
+) -> core::fmt::Result
+where
+    I: DoubleEndedIterator<Item = (&'a String, &'a BNode)> + ExactSizeIterator,
+{
+    f.write_str("Dict {\n")?;
+    stack.push(PrettyWork::Close {
+        bracket: '}',
+        indent,
+        last,
+    });
+    let len = entries.len();
+    stack.extend(
+        entries
+            .enumerate()
+            .rev()
+            .map(|(i, (k, node))| PrettyWork::Node {
+                node,
+                key: Some(k),
+                indent: indent + 1,
+                last: i == len - 1,
+            }),
+    );
+    Ok(())
+}
+
+impl Display for PrettyDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut stack = vec![PrettyWork::Node {
+            node: self.0,
+            key: None,
+            indent: 0,
+            last: true,
+        }];
+
+        while let Some(work) = stack.pop() {
+            match work {
+                PrettyWork::Node {
+                    node,
+                    key,
+                    indent,
+                    last,
+                } => {
+                    write!(f, "{:width$}", "", width = indent * 2)?;
+                    if let Some(key) = key {
+                        write!(f, "{:?}: ", key)?;
+                    }
+
+                    match node {
+                        BNode::Integer(i) => write!(f, "Integer({})", i)?,
+                        #[cfg(feature = "bigint")]
+                        BNode::BigInteger(i) => write!(f, "BigInteger({})", i)?,
+                        BNode::Bytes(bytes) => match core::str::from_utf8(bytes) {
+                            Ok(s) => write!(f, "Bytes({:?})", s)?,
+                            Err(_) => {
+                                write!(f, "Bytes(<{} bytes: 0x", bytes.len())?;
+                                for byte in bytes {
+                                    write!(f, "{:02X}", byte)?;
+                                }
+                                f.write_str(">)")?;
+                            }
+                        },
+                        BNode::List(list) if list.is_empty() => f.write_str("List []")?,
+                        BNode::Dict(dict) if dict.is_empty() => f.write_str("Dict {}")?,
+                        #[cfg(feature = "indexmap")]
+                        BNode::OrderedDict(dict) if dict.is_empty() => f.write_str("Dict {}")?,
+                        BNode::List(list) => {
+                            f.write_str("List [\n")?;
+                            stack.push(PrettyWork::Close {
+                                bracket: ']',
+                                indent,
+                                last,
+                            });
+                            stack.extend(list.iter().enumerate().rev().map(|(i, node)| {
+                                PrettyWork::Node {
+                                    node,
+                                    key: None,
+                                    indent: indent + 1,
+                                    last: i == list.len() - 1,
+                                }
+                            }));
+                            continue;
+                        }
+                        BNode::Dict(dict) => {
+                            open_dict(f, &mut stack, dict.iter(), indent, last)?;
+                            continue;
+                        }
+                        #[cfg(feature = "indexmap")]
+                        BNode::OrderedDict(dict) => {
+                            open_dict(f, &mut stack, dict.iter(), indent, last)?;
+                            continue;
+                        }
+                    }
+                    end_line(f, indent, last)?;
+                }
+                PrettyWork::Close {
+                    bracket,
+                    indent,
+                    last,
+                } => {
+                    write!(f, "{:width$}{}", "", bracket, width = indent * 2)?;
+                    end_line(f, indent, last)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the `Debug` form of `node`, nested `depth` containers deep. The
+/// depth limit also bounds the recursion.
+fn debug_node(node: &BNode, f: &mut Formatter<'_>, depth: usize) -> core::fmt::Result {
+    match node {
+        BNode::Integer(i) => write!(f, "Integer({})", i),
+        #[cfg(feature = "bigint")]
+        BNode::BigInteger(i) => write!(f, "BigInteger({})", i),
+        BNode::Bytes(bytes) => match core::str::from_utf8(bytes) {
+            Ok(s) => write!(f, "Bytes({:?})", s),
+            Err(_) => {
+                write!(f, "Bytes(<{} bytes: ", bytes.len())?;
+                for byte in bytes.iter().take(DEBUG_MAX_HEX_BYTES) {
+                    write!(f, "{:02x}", byte)?;
+                }
+                if bytes.len() > DEBUG_MAX_HEX_BYTES {
+                    f.write_str("…")?;
+                }
+                f.write_str(">)")
+            }
+        },
+        BNode::List(list) if depth >= DEBUG_MAX_DEPTH => write!(f, "List(<{} items>)", list.len()),
+        BNode::List(list) => {
+            f.write_str("List([")?;
+            for (i, node) in list.iter().take(DEBUG_MAX_LIST_ITEMS).enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                debug_node(node, f, depth + 1)?;
+            }
+            f.write_str("]")?;
+            if list.len() > DEBUG_MAX_LIST_ITEMS {
+                write!(f, " + {} more items", list.len() - DEBUG_MAX_LIST_ITEMS)?;
+            }
+            f.write_str(")")
+        }
+        BNode::Dict(dict) => debug_dict(f, "Dict", dict.iter(), depth),
+        #[cfg(feature = "indexmap")]
+        BNode::OrderedDict(dict) => debug_dict(f, "OrderedDict", dict.iter(), depth),
+    }
+}
+
+fn debug_dict<'a, I>(
+    f: &mut Formatter<'_>,
+    name: &str,
+    entries: I,
+    depth: usize,
+) -> core::fmt::Result
+where
+    I: ExactSizeIterator<Item = (&'a String, &'a BNode)>,
+{
+    let len = entries.len();
+    if depth >= DEBUG_MAX_DEPTH {
+        return write!(f, "{}(<{} entries>)", name, len);
+    }
+
+    write!(f, "{}({{", name)?;
+    for (i, (key, node)) in entries.take(DEBUG_MAX_DICT_ENTRIES).enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{:?}: ", key)?;
+        debug_node(node, f, depth + 1)?;
+    }
+    f.write_str("}")?;
+    if len > DEBUG_MAX_DICT_ENTRIES {
+        write!(f, " + {} more entries", len - DEBUG_MAX_DICT_ENTRIES)?;
+    }
+    f.write_str(")")
+}
+
+/// Shows byte strings as text when they are valid UTF-8 and in hex otherwise,
+/// e.g. `Bytes("hello")` or `Bytes(<2 bytes: ab0c>)`, the hex stopping after
+/// 32 bytes. Lists are cut after 10 elements, dictionaries after 5 entries and
+/// containers nested more than 5 levels deep only show their size, so large
+/// trees stay readable. Use [`PrettyDisplay`] to show a whole tree.
+impl Debug for BNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        debug_node(self, f, 0)
+    }
+}
+
+enum CompactWork<'a> {
+    Node(&'a BNode),
+    Text(&'a str),
+}
+
+/// Schedules the entries of a dictionary, in the order of `entries`, and its
+/// closing brace.
+fn push_compact_entries<'a, I>(stack: &mut Vec<CompactWork<'a>>, entries: I)
+where
+    I: DoubleEndedIterator<Item = (&'a String, &'a BNode)> + ExactSizeIterator,
+{
+    stack.push(CompactWork::Text("}"));
+    for (i, (key, node)) in entries.enumerate().rev() {
+        stack.push(CompactWork::Node(node));
+        stack.push(CompactWork::Text(": "));
+        stack.push(CompactWork::Text(key));
+        if i > 0 {
+            stack.push(CompactWork::Text(", "));
+        }
+    }
+}
+
+impl BNode {
+    /// Formats the node with [`PrettyDisplay`].
+    pub fn pretty_print(&self) -> String {
+        PrettyDisplay(self).to_string()
+    }
+
+    /// Formats the node on a single line, e.g. `{info: {length: 1, name: "a"}}`.
+    /// Byte strings which are not valid UTF-8 show as `<N bytes>`.
+    pub fn to_compact_string(&self) -> String {
+        let mut out = String::new();
+        let mut stack = vec![CompactWork::Node(self)];
+
+        while let Some(work) = stack.pop() {
+            match work {
+                CompactWork::Text(text) => out.push_str(text),
+                CompactWork::Node(BNode::Integer(i)) => {
+                    let _ = write!(out, "{}", i);
+                }
+                #[cfg(feature = "bigint")]
+                CompactWork::Node(BNode::BigInteger(i)) => {
+                    let _ = write!(out, "{}", i);
+                }
+                CompactWork::Node(BNode::Bytes(bytes)) => {
+                    let _ = match core::str::from_utf8(bytes) {
+                        Ok(s) => write!(out, "{:?}", s),
+                        Err(_) => write!(out, "<{} bytes>", bytes.len()),
+                    };
+                }
+                CompactWork::Node(BNode::List(list)) => {
+                    out.push('[');
+                    stack.push(CompactWork::Text("]"));
+                    for (i, node) in list.iter().enumerate().rev() {
+                        stack.push(CompactWork::Node(node));
+                        if i > 0 {
+                            stack.push(CompactWork::Text(", "));
+                        }
+                    }
+                }
+                CompactWork::Node(BNode::Dict(dict)) => {
+                    out.push('{');
+                    push_compact_entries(&mut stack, dict.iter());
+                }
+                #[cfg(feature = "indexmap")]
+                CompactWork::Node(BNode::OrderedDict(dict)) => {
+                    out.push('{');
+                    push_compact_entries(&mut stack, dict.iter());
+                }
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_from_slice, parse_from_str, BNode};
+
+    #[test]
+    fn test_pretty_print() {
+        let bnode =
+            parse_from_str("d8:announce8:http://a4:infod6:lengthi351272960e5:pathsl1:a1:beee")
+                .unwrap();
+
+        assert_eq!(
+            r#"Dict {
+  "announce": Bytes("http://a"),
+  "info": Dict {
+    "length": Integer(351272960),
+    "paths": List [
+      Bytes("a"),
+      Bytes("b")
+    ]
+  }
+}"#,
+            bnode.pretty_print()
+        );
+    }
+
+    #[test]
+    fn test_pretty_print_scalars_and_empty() {
+        assert_eq!("Integer(-1)", BNode::Integer(-1).pretty_print());
+        assert_eq!("Bytes(\"a\\\"b\")", BNode::from("a\"b").pretty_print());
+        assert_eq!("List []", BNode::List(vec![]).pretty_print());
+        assert_eq!(
+            "List [\n  Dict {},\n  List []\n]",
+            parse_from_str("ldelee").unwrap().pretty_print()
+        );
+    }
+
+    #[test]
+    fn test_pretty_print_binary() {
+        let bnode = parse_from_slice(b"d4:hash2:\xab\x0ce").unwrap();
+
+        assert_eq!(
+            "Dict {\n  \"hash\": Bytes(<2 bytes: 0xAB0C>)\n}",
+            bnode.pretty_print()
+        );
+    }
+
+    #[test]
+    fn test_to_compact_string() {
+        let bnode =
+            parse_from_str("d8:announce8:http://a4:infod6:lengthi12345e4:name4:fileee").unwrap();
+        assert_eq!(
+            r#"{announce: "http://a", info: {length: 12345, name: "file"}}"#,
+            bnode.to_compact_string()
+        );
+
+        let bnode = parse_from_slice(b"li-1e2:\xab\x0cl0:1:\"eldeee").unwrap();
+        assert_eq!(
+            r#"[-1, <2 bytes>, ["", "\""], [{}]]"#,
+            bnode.to_compact_string()
+        );
+        assert_eq!("[]", BNode::List(vec![]).to_compact_string());
+        assert_eq!("{}", parse_from_str("de").unwrap().to_compact_string());
+    }
+
+    #[test]
+    fn test_debug() {
+        let bnode = parse_from_str("d4:infod6:lengthi42e4:name5:helloe4:tagsl5:linuxee").unwrap();
+        assert_eq!(
+            r#"Dict({"info": Dict({"length": Integer(42), "name": Bytes("hello")}), "tags": List([Bytes("linux")])})"#,
+            format!("{:?}", bnode)
+        );
+        assert_eq!(
+            "Bytes(<2 bytes: ab0c>)",
+            format!("{:?}", BNode::Bytes(vec![0xab, 0x0c]))
+        );
+        assert_eq!("List([])", format!("{:?}", BNode::List(vec![])));
+    }
+
+    #[test]
+    fn test_debug_truncated() {
+        let list = BNode::List((0..13).map(BNode::Integer).collect());
+        assert_eq!(
+            "List([Integer(0), Integer(1), Integer(2), Integer(3), Integer(4), \
+             Integer(5), Integer(6), Integer(7), Integer(8), Integer(9)] + 3 more items)",
+            format!("{:?}", list)
+        );
+
+        let dict = parse_from_str("d1:ai1e1:bi2e1:ci3e1:di4e1:ei5e1:fi6e1:gi7ee").unwrap();
+        assert_eq!(
+            r#"Dict({"a": Integer(1), "b": Integer(2), "c": Integer(3), "d": Integer(4), "e": Integer(5)} + 2 more entries)"#,
+            format!("{:?}", dict)
+        );
+
+        let hash = BNode::Bytes((0..40).map(|i| 0xc0 + i).collect());
+        assert_eq!(
+            "Bytes(<40 bytes: c0c1c2c3c4c5c6c7c8c9cacbcccdcecfd0d1d2d3d4d5d6d7d8d9dadbdcdddedf…>)",
+            format!("{:?}", hash)
+        );
+
+        let deep = parse_from_str("lllllli1eeeeeee").unwrap();
+        assert_eq!(
+            "List([List([List([List([List([List(<1 items>)])])])])])",
+            format!("{:?}", deep)
+        );
+    }
+}
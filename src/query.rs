@@ -0,0 +1,296 @@
+use crate::prelude::*;
+use crate::{BNode, Error, ErrorKind, Result};
+
+#[derive(Debug, PartialEq, Eq)]
+enum QueryStep {
+    /// `.key`
+    Key(String),
+    /// `.[n]` or `[n]`
+    Index(usize),
+    /// `[*]` or `.[*]`
+    All,
+    /// `..key`
+    Descend(String),
+}
+
+/// Parses the steps of a query expression:
+///
+/// ```text
+/// expr  := ('/'? step)*
+/// step  := '..' key | '.' key | '.'? '[' (index | '*') ']'
+/// key   := any bytes up to the next '.', '/' or '['
+/// ```
+struct QueryParser<'a> {
+    expr: &'a [u8],
+    pos: usize,
+}
+
+impl QueryParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.expr.get(self.pos).copied()
+    }
+
+    fn error(&self) -> Error {
+        match self.peek() {
+            Some(byte) => Error::new(ErrorKind::InvalidToken(byte), self.pos as u64),
+            None => Error::new(ErrorKind::UnexpectedEof, self.pos as u64),
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.peek() != Some(byte) {
+            return Err(self.error());
+        }
+
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_steps(&mut self) -> Result<Vec<QueryStep>> {
+        let mut steps = vec![];
+
+        while self.peek().is_some() {
+            if self.peek() == Some(b'/') {
+                self.pos += 1;
+            }
+            steps.push(self.parse_step()?);
+        }
+
+        Ok(steps)
+    }
+
+    fn parse_step(&mut self) -> Result<QueryStep> {
+        match self.peek() {
+            Some(b'[') => self.parse_bracket(),
+            Some(b'.') => {
+                self.pos += 1;
+                match self.peek() {
+                    Some(b'.') => {
+                        self.pos += 1;
+                        Ok(QueryStep::Descend(self.parse_key()?))
+                    }
+                    Some(b'[') => self.parse_bracket(),
+                    _ => Ok(QueryStep::Key(self.parse_key()?)),
+                }
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String> {
+        let start = self.pos;
+        while !matches!(self.peek(), None | Some(b'.' | b'/' | b'[')) {
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            return Err(self.error());
+        }
+
+        // The expression is a `str` and keys end at ASCII bytes, so a key is
+        // valid UTF-8 on its own.
+        Ok(String::from_utf8(self.expr[start..self.pos].to_vec()).unwrap())
+    }
+
+    fn parse_bracket(&mut self) -> Result<QueryStep> {
+        self.expect(b'[')?;
+
+        let step = if self.peek() == Some(b'*') {
+            self.pos += 1;
+            QueryStep::All
+        } else {
+            let start = self.pos;
+            let mut index: usize = 0;
+            while let Some(digit @ b'0'..=b'9') = self.peek() {
+                index = match index
+                    .checked_mul(10)
+                    .and_then(|index| index.checked_add((digit - b'0') as usize))
+                {
+                    Some(index) => index,
+                    None => throw!(ErrorKind::IntegerOverflow, self.pos as u64),
+                };
+                self.pos += 1;
+            }
+
+            if start == self.pos {
+                return Err(self.error());
+            }
+            QueryStep::Index(index)
+        };
+
+        self.expect(b']')?;
+        Ok(step)
+    }
+}
+
+/// Appends the values of `key` in `node` and all of its descendants, in
+/// depth-first pre-order.
+fn descend<'a>(node: &'a BNode, key: &str, found: &mut Vec<&'a BNode>) {
+    let mut stack = vec![node];
+
+    while let Some(node) = stack.pop() {
+        match node {
+            BNode::List(list) => stack.extend(list.iter().rev()),
+            BNode::Dict(dict) => {
+                if let Some(value) = dict.get(key) {
+                    found.push(value);
+                }
+                stack.extend(dict.values().rev());
+            }
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => {
+                if let Some(value) = dict.get(key) {
+                    found.push(value);
+                }
+                stack.extend(dict.values().rev());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl BNode {
+    /// Selects the nodes matching `expr`, e.g. `.info.files[*].length`:
+    ///
+    /// - `.key` selects the value of `key` in a dictionary.
+    /// - `.[n]` or `[n]` selects the `n`-th element of a list.
+    /// - `[*]` selects all elements of a list.
+    /// - `..key` selects the values of `key` in dictionaries at any depth.
+    ///
+    /// Steps can also be separated with `/`, as in `.info/.name`. Steps which
+    /// don't apply to a node select nothing from it. A malformed expression
+    /// fails with an error positioned at the offending byte of `expr`.
+    pub fn query<'a>(&'a self, expr: &str) -> Result<Vec<&'a BNode>> {
+        let steps = QueryParser {
+            expr: expr.as_bytes(),
+            pos: 0,
+        }
+        .parse_steps()?;
+        let mut selected = vec![self];
+
+        for step in &steps {
+            let mut next = vec![];
+            for node in selected {
+                match (step, node) {
+                    (QueryStep::Key(key), node) => next.extend(node.get(key)),
+                    (QueryStep::Index(index), BNode::List(list)) => next.extend(list.get(*index)),
+                    (QueryStep::All, BNode::List(list)) => next.extend(list),
+                    (QueryStep::Descend(key), node) => descend(node, key, &mut next),
+                    _ => {}
+                }
+            }
+            selected = next;
+        }
+
+        Ok(selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QueryParser, QueryStep};
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{parse_from_str, BNode, ErrorKind};
+
+    #[test]
+    fn test_parse_query() {
+        let mut parser = QueryParser {
+            expr: b".info..name/.[1][*]/[2]",
+            pos: 0,
+        };
+        assert_eq!(
+            vec![
+                QueryStep::Key("info".into()),
+                QueryStep::Descend("name".into()),
+                QueryStep::Index(1),
+                QueryStep::All,
+                QueryStep::Index(2),
+            ],
+            parser.parse_steps().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_query() {
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+
+        assert_eq!(
+            vec![&BNode::from("debian-10.2.0-amd64-netinst.iso")],
+            torrent.query(".info.name").unwrap()
+        );
+        assert_eq!(
+            torrent.query(".info.name").unwrap(),
+            torrent.query(".info/.name").unwrap()
+        );
+        assert_eq!(vec![&torrent], torrent.query("").unwrap());
+
+        let seeds = torrent.query(".httpseeds[*]").unwrap();
+        assert_eq!(2, seeds.len());
+        assert_eq!(vec![seeds[1]], torrent.query(".httpseeds.[1]").unwrap());
+        assert!(torrent.query(".httpseeds[2]").unwrap().is_empty());
+
+        assert_eq!(
+            vec![&BNode::Integer(351272960)],
+            torrent.query("..length").unwrap()
+        );
+        assert_eq!(
+            vec![&BNode::Integer(262144)],
+            torrent.query(".info..piece length").unwrap()
+        );
+        assert!(torrent.query(".missing.name").unwrap().is_empty());
+        assert!(torrent.query(".announce[*]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_query_nested() {
+        let bnode =
+            parse_from_str("d5:filesld6:lengthi1eed6:lengthi2eed4:subsld6:lengthi3eeeeee").unwrap();
+
+        assert_eq!(
+            vec![&BNode::Integer(1), &BNode::Integer(2), &BNode::Integer(3)],
+            bnode.query("..length").unwrap()
+        );
+        assert_eq!(
+            vec![&BNode::Integer(1), &BNode::Integer(2)],
+            bnode.query(".files[*].length").unwrap()
+        );
+        assert_eq!(
+            vec![&BNode::Integer(3)],
+            bnode.query(".files/[2]/..length").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_query_failed() {
+        let bnode = parse_from_str("de").unwrap();
+        let cases = [
+            ("info", ErrorKind::InvalidToken(b'i'), 0),
+            (".", ErrorKind::UnexpectedEof, 1),
+            (".a..", ErrorKind::UnexpectedEof, 4),
+            (".[x]", ErrorKind::InvalidToken(b'x'), 2),
+            (".[1", ErrorKind::UnexpectedEof, 3),
+            ("[]", ErrorKind::InvalidToken(b']'), 1),
+            ("[99999999999999999999999]", ErrorKind::IntegerOverflow, 20),
+        ];
+        for (expr, kind, position) in cases {
+            let err = bnode.query(expr).unwrap_err();
+            assert_eq!(&kind, err.kind(), "{}", expr);
+            assert_eq!(position, err.position, "{}", expr);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_query_ordered_dict() {
+        let torrent = crate::parse_ordered(&mut DEBIAN_TORRENT.bytes()).unwrap();
+
+        assert_eq!(
+            vec![&BNode::from("debian-10.2.0-amd64-netinst.iso")],
+            torrent.query(".info.name").unwrap()
+        );
+        assert_eq!(
+            vec![&BNode::Integer(351272960)],
+            torrent.query("..length").unwrap()
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::prelude::*;
+use crate::reader::{read_single, NodeSink, Strings};
+use crate::{BDict, BNode, Error, ErrorKind, Lexer, Result};
+use alloc::collections::BTreeMap;
+use core::ops::Range;
+
+pub type BDictRaw = BTreeMap<Vec<u8>, BNodeRaw>;
+
+/// Like [`BNode`], but dictionary keys are kept as bytes, since bencode
+/// doesn't require them to be UTF-8.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BNodeRaw {
+    Integer(i64),
+    #[cfg(feature = "bigint")]
+    BigInteger(Box<num_bigint::BigInt>),
+    Bytes(Vec<u8>),
+    List(Vec<BNodeRaw>),
+    Dict(BDictRaw),
+}
+
+enum StringKeyedWork {
+    Convert(BNodeRaw),
+    Key(Vec<u8>),
+    /// Collects the given number of converted elements into a list.
+    List(usize),
+    /// Collects the given number of converted keys and values into a
+    /// dictionary.
+    Dict(usize),
+}
+
+impl BNodeRaw {
+    /// Converts into a [`BNode`], failing on the first dictionary key which is
+    /// not valid UTF-8.
+    pub fn into_string_keyed(self) -> Result<BNode> {
+        // Pending conversions are kept on the heap instead of the call stack,
+        // so deeply nested nodes cannot overflow it.
+        let mut work = vec![StringKeyedWork::Convert(self)];
+        let mut keys: Vec<String> = vec![];
+        let mut converted: Vec<BNode> = vec![];
+
+        while let Some(item) = work.pop() {
+            match item {
+                StringKeyedWork::Convert(BNodeRaw::Integer(i)) => converted.push(BNode::Integer(i)),
+                #[cfg(feature = "bigint")]
+                StringKeyedWork::Convert(BNodeRaw::BigInteger(i)) => {
+                    converted.push(BNode::BigInteger(i))
+                }
+                StringKeyedWork::Convert(BNodeRaw::Bytes(bytes)) => {
+                    converted.push(BNode::Bytes(bytes))
+                }
+                StringKeyedWork::Convert(BNodeRaw::List(list)) => {
+                    work.push(StringKeyedWork::List(list.len()));
+                    work.extend(list.into_iter().rev().map(StringKeyedWork::Convert));
+                }
+                StringKeyedWork::Convert(BNodeRaw::Dict(dict)) => {
+                    work.push(StringKeyedWork::Dict(dict.len()));
+                    for (key, value) in dict.into_iter().rev() {
+                        work.push(StringKeyedWork::Convert(value));
+                        work.push(StringKeyedWork::Key(key));
+                    }
+                }
+                StringKeyedWork::Key(key) => match String::from_utf8(key) {
+                    Ok(key) => keys.push(key),
+                    Err(e) => {
+                        let hex: String =
+                            e.as_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+                        throw!(
+                            ErrorKind::Custom(format!(
+                                "dictionary key 0x{} is not valid UTF-8",
+                                hex
+                            )),
+                            Error::NO_POSITION
+                        )
+                    }
+                },
+                StringKeyedWork::List(len) => {
+                    let list = converted.split_off(converted.len() - len);
+                    converted.push(list.into());
+                }
+                StringKeyedWork::Dict(len) => {
+                    let values = converted.split_off(converted.len() - len);
+                    let dict: BDict = keys
+                        .split_off(keys.len() - len)
+                        .into_iter()
+                        .zip(values)
+                        .collect();
+                    converted.push(dict.into());
+                }
+            }
+        }
+
+        Ok(converted.pop().unwrap())
+    }
+}
+
+enum Frame {
+    List(Vec<BNodeRaw>),
+    Dict(BDictRaw, Option<Vec<u8>>),
+}
+
+/// Builds [`BNodeRaw`]s, taking any key.
+struct RawSink;
+
+impl Strings for RawSink {
+    type Bytes = Vec<u8>;
+    type Key = Vec<u8>;
+
+    fn read_bytes<T>(&mut self, lexer: &mut Lexer<'_, T>, len: usize) -> Result<Vec<u8>>
+    where
+        T: Iterator<Item = u8>,
+    {
+        lexer.read_bytes(len)
+    }
+
+    fn make_key(&mut self, bytes: Vec<u8>) -> Option<Vec<u8>> {
+        Some(bytes)
+    }
+}
+
+impl NodeSink for RawSink {
+    type Node = BNodeRaw;
+    type Frame = Frame;
+
+    fn integer(&mut self, value: i64, _: Range<u64>) -> BNodeRaw {
+        BNodeRaw::Integer(value)
+    }
+
+    #[cfg(feature = "bigint")]
+    fn big_integer(&mut self, value: Box<num_bigint::BigInt>, _: Range<u64>) -> BNodeRaw {
+        BNodeRaw::BigInteger(value)
+    }
+
+    fn bytes(&mut self, bytes: Vec<u8>, _: Range<u64>) -> BNodeRaw {
+        BNodeRaw::Bytes(bytes)
+    }
+
+    fn list(&mut self, _: u64) -> Frame {
+        Frame::List(vec![])
+    }
+
+    fn dict(&mut self, _: u64) -> Frame {
+        Frame::Dict(BDictRaw::new(), None)
+    }
+
+    fn key(&mut self, frame: &mut Frame, key: Vec<u8>) {
+        if let Frame::Dict(_, pending) = frame {
+            *pending = Some(key);
+        }
+    }
+
+    fn push(&mut self, frame: &mut Frame, node: BNodeRaw) {
+        match frame {
+            Frame::List(list) => list.push(node),
+            Frame::Dict(dict, key) => {
+                dict.insert(key.take().unwrap(), node);
+            }
+        }
+    }
+
+    fn end(&mut self, frame: Frame, _: u64) -> BNodeRaw {
+        match frame {
+            Frame::List(list) => BNodeRaw::List(list),
+            Frame::Dict(dict, _) => BNodeRaw::Dict(dict),
+        }
+    }
+}
+
+/// Parses a single value keeping dictionary keys as bytes, see [`BNodeRaw`].
+pub fn parse_raw<T: Iterator<Item = u8>>(stream: &mut T) -> Result<BNodeRaw> {
+    read_single(&mut Lexer::new(stream), &mut RawSink)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_raw, BNodeRaw};
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{parse_from_str, BNode, ErrorKind};
+
+    #[test]
+    fn test_parse_raw_binary_keys() {
+        let raw = b"d2:\xff\xfei1e3:fooli2eee";
+        let node = parse_raw(&mut raw.iter().copied()).unwrap();
+
+        match &node {
+            BNodeRaw::Dict(dict) => {
+                assert_eq!(Some(&BNodeRaw::Integer(1)), dict.get(&b"\xff\xfe"[..]));
+                assert_eq!(
+                    Some(&BNodeRaw::List(vec![BNodeRaw::Integer(2)])),
+                    dict.get(&b"foo"[..])
+                );
+            }
+            _ => panic!("should be a dictionary"),
+        }
+
+        let err = node.into_string_keyed().unwrap_err();
+        assert_eq!(
+            &ErrorKind::Custom("dictionary key 0xfffe is not valid UTF-8".to_string()),
+            err.kind()
+        );
+    }
+
+    #[test]
+    fn test_parse_raw_into_string_keyed() {
+        let node = parse_raw(&mut DEBIAN_TORRENT.bytes()).unwrap();
+
+        assert_eq!(
+            parse_from_str(DEBIAN_TORRENT).unwrap(),
+            node.into_string_keyed().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_parse_raw_big_integer() {
+        let raw = "d3:fooi-99999999999999999999ee";
+        let node = parse_raw(&mut raw.bytes()).unwrap();
+
+        assert_eq!(
+            parse_from_str(raw).unwrap(),
+            node.into_string_keyed().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_into_string_keyed_deeply_nested() {
+        let depth = 5000;
+        let raw = "d1:a".repeat(depth) + "le" + &"e".repeat(depth);
+
+        let mut node = parse_raw(&mut raw.bytes())
+            .unwrap()
+            .into_string_keyed()
+            .unwrap();
+        for _ in 0..depth {
+            node = node.into_dict().unwrap().remove("a").unwrap();
+        }
+        assert_eq!(BNode::List(vec![]), node);
+    }
+
+    #[test]
+    fn test_parse_raw_failed() {
+        let cases = ["", "5:halo", "i1ei2e", "l4:halo", "di1ei2ee"];
+        for x in &cases {
+            if parse_raw(&mut x.bytes()).is_ok() {
+                panic!("`{}` should fail", x);
+            }
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use crate::prelude::*;
+use crate::{
+    exceeds, unexpected, Error, ErrorKind, LenientConfig, Lexer, ParserConfig, Result, Token,
+};
+use alloc::collections::BTreeSet;
+use core::ops::Range;
+
+/// How the byte strings and dictionary keys of the input are represented.
+pub(crate) trait Strings {
+    type Bytes;
+    type Key: AsRef<[u8]>;
+
+    /// Takes the `len` bytes of a byte string following its [`Token::Colon`].
+    fn read_bytes<T>(&mut self, lexer: &mut Lexer<'_, T>, len: usize) -> Result<Self::Bytes>
+    where
+        T: Iterator<Item = u8>;
+
+    /// Turns the bytes of a dictionary key into a key, `None` if they aren't
+    /// valid UTF-8 and have to be.
+    fn make_key(&mut self, bytes: Self::Bytes) -> Option<Self::Key>;
+}
+
+/// Copies byte strings and requires UTF-8 keys, like [`BNode`](crate::BNode).
+pub(crate) struct Copied;
+
+impl Strings for Copied {
+    type Bytes = Vec<u8>;
+    type Key = String;
+
+    fn read_bytes<T>(&mut self, lexer: &mut Lexer<'_, T>, len: usize) -> Result<Vec<u8>>
+    where
+        T: Iterator<Item = u8>,
+    {
+        lexer.read_bytes(len)
+    }
+
+    fn make_key(&mut self, bytes: Vec<u8>) -> Option<String> {
+        String::from_utf8(bytes).ok()
+    }
+}
+
+/// A step through the input reported by [`Reader::next_item`].
+pub(crate) enum Item<B, K> {
+    Integer(i64),
+    #[cfg(feature = "bigint")]
+    BigInteger(Box<num_bigint::BigInt>),
+    Bytes(B),
+    Key(K),
+    StartList,
+    EndList,
+    StartDict,
+    EndDict,
+}
+
+enum Frame {
+    List(usize),
+    /// The number of entries, whether the next item is a key rather than a
+    /// value, and the keys so far if they are checked.
+    Dict(usize, bool, Option<BTreeSet<Vec<u8>>>),
+}
+
+/// The parser shared by every representation of a node: it validates the
+/// input against a [`ParserConfig`] and reports it item by item, leaving the
+/// building of nodes to the caller, see [`Builder`].
+///
+/// Tokens are never looked ahead, so between two items nothing of the next
+/// one has been consumed yet.
+pub(crate) struct Reader {
+    config: ParserConfig,
+    lenient: Option<LenientConfig>,
+    /// The violations tolerated so far, see [`Reader::with_lenient_config`].
+    pub(crate) warnings: Vec<Error>,
+    stack: Vec<Frame>,
+}
+
+impl Reader {
+    pub(crate) fn new(config: ParserConfig) -> Reader {
+        Reader {
+            config,
+            lenient: None,
+            warnings: vec![],
+            stack: vec![],
+        }
+    }
+
+    /// Checks the order of dictionary keys, recording the violations allowed
+    /// by `lenient` instead of failing. Leading zeros are up to the lexer.
+    pub(crate) fn with_lenient_config(lenient: LenientConfig) -> Reader {
+        let mut reader = Reader::new(ParserConfig::default());
+        reader.lenient = Some(lenient);
+        reader
+    }
+
+    /// The number of lists and dictionaries still open.
+    pub(crate) fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub(crate) fn next_item<S, T>(
+        &mut self,
+        lexer: &mut Lexer<'_, T>,
+        strings: &mut S,
+    ) -> Result<Item<S::Bytes, S::Key>>
+    where
+        S: Strings,
+        T: Iterator<Item = u8>,
+    {
+        let item = match (self.stack.last(), lexer.next_token()?) {
+            (Some(Frame::Dict(_, true, _)), Token::Length(len)) => {
+                let bytes = self.read_bytes(lexer, strings, len)?;
+                let key = match strings.make_key(bytes) {
+                    Some(key) => key,
+                    None => throw!(ErrorKind::DictKeyNotUtf8, lexer.position()),
+                };
+                self.check_key(key.as_ref(), lexer.position())?;
+                return Ok(Item::Key(key));
+            }
+            (Some(Frame::Dict(_, true, _)), Token::End) => {
+                self.stack.pop();
+                Item::EndDict
+            }
+            (Some(Frame::Dict(_, true, _)), found) => {
+                throw!(unexpected("dictionary key or `e`", found), lexer.position())
+            }
+            (_, Token::IntegerBegin) => {
+                let (value, read) = lexer.read_integer_before(0, b'e', true)?;
+                if read < 1 {
+                    throw!(ErrorKind::EmptyInteger, lexer.position())
+                }
+
+                let end = lexer.next_token()?;
+                debug_assert_eq!(Token::End, end);
+                match value {
+                    crate::BNode::Integer(value) => Item::Integer(value),
+                    #[cfg(feature = "bigint")]
+                    crate::BNode::BigInteger(value) => Item::BigInteger(value),
+                    _ => unreachable!(),
+                }
+            }
+            (_, Token::Length(len)) => Item::Bytes(self.read_bytes(lexer, strings, len)?),
+            (_, Token::ListBegin) => {
+                self.enter(Frame::List(0), lexer.position())?;
+                return Ok(Item::StartList);
+            }
+            (_, Token::DictBegin) => {
+                let keys =
+                    (self.config.strict_dict_keys || self.lenient.is_some()).then(BTreeSet::new);
+                self.enter(Frame::Dict(0, true, keys), lexer.position())?;
+                return Ok(Item::StartDict);
+            }
+            (Some(Frame::List(_)), Token::End) => {
+                self.stack.pop();
+                Item::EndList
+            }
+            (Some(Frame::List(_)), found) => {
+                throw!(unexpected("list element or `e`", found), lexer.position())
+            }
+            (_, found) => throw!(unexpected("value", found), lexer.position()),
+        };
+
+        // A value is complete, so a surrounding dictionary expects a key next.
+        match self.stack.last_mut() {
+            None => {}
+            Some(Frame::List(len)) => {
+                *len += 1;
+                if exceeds(self.config.max_list_elements, *len) {
+                    throw!(
+                        ErrorKind::LimitExceeded {
+                            limit: "list elements",
+                            max: self.config.max_list_elements.unwrap_or_default(),
+                        },
+                        lexer.position()
+                    )
+                }
+            }
+            Some(Frame::Dict(len, expect_key, _)) => {
+                *len += 1;
+                *expect_key = true;
+                if exceeds(self.config.max_dict_entries, *len) {
+                    throw!(
+                        ErrorKind::LimitExceeded {
+                            limit: "dictionary entries",
+                            max: self.config.max_dict_entries.unwrap_or_default(),
+                        },
+                        lexer.position()
+                    )
+                }
+            }
+        }
+
+        Ok(item)
+    }
+
+    fn read_bytes<S, T>(
+        &self,
+        lexer: &mut Lexer<'_, T>,
+        strings: &mut S,
+        len: i64,
+    ) -> Result<S::Bytes>
+    where
+        S: Strings,
+        T: Iterator<Item = u8>,
+    {
+        if exceeds(self.config.max_bytes_len, len as usize) {
+            throw!(
+                ErrorKind::LimitExceeded {
+                    limit: "byte string length",
+                    max: self.config.max_bytes_len.unwrap_or_default(),
+                },
+                lexer.position()
+            )
+        }
+
+        let colon = lexer.next_token()?;
+        debug_assert_eq!(Token::Colon, colon);
+        strings.read_bytes(lexer, len as usize)
+    }
+
+    fn check_key(&mut self, key: &[u8], position: u64) -> Result<()> {
+        let keys = match self.stack.last_mut() {
+            Some(Frame::Dict(_, expect_key, keys)) => {
+                *expect_key = false;
+                match keys {
+                    Some(keys) => keys,
+                    None => return Ok(()),
+                }
+            }
+            _ => unreachable!(),
+        };
+
+        let lenient = self.lenient.clone().unwrap_or(LenientConfig {
+            leading_zeros: false,
+            unsorted_dict_keys: false,
+            duplicate_dict_keys: false,
+        });
+        let violation = if keys.contains(key) {
+            Some((ErrorKind::DuplicateDictKey, lenient.duplicate_dict_keys))
+        } else if matches!(keys.last(), Some(prev) if prev.as_slice() > key) {
+            Some((ErrorKind::DictKeyOutOfOrder, lenient.unsorted_dict_keys))
+        } else {
+            None
+        };
+        keys.insert(key.to_vec());
+
+        match violation {
+            Some((kind, false)) => throw!(kind, position),
+            Some((kind, true)) => self.warnings.push(Error::new(kind, position)),
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn enter(&mut self, frame: Frame, position: u64) -> Result<()> {
+        self.stack.push(frame);
+        if exceeds(self.config.max_depth, self.stack.len()) {
+            throw!(
+                ErrorKind::LimitExceeded {
+                    limit: "nesting depth",
+                    max: self.config.max_depth.unwrap_or_default(),
+                },
+                position
+            )
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds one representation of a node out of the items of a [`Reader`].
+pub(crate) trait NodeSink: Strings {
+    type Node;
+    /// A list or dictionary whose elements are still being read.
+    type Frame;
+
+    fn integer(&mut self, value: i64, span: Range<u64>) -> Self::Node;
+
+    #[cfg(feature = "bigint")]
+    fn big_integer(&mut self, value: Box<num_bigint::BigInt>, span: Range<u64>) -> Self::Node;
+
+    fn bytes(&mut self, bytes: Self::Bytes, span: Range<u64>) -> Self::Node;
+
+    fn list(&mut self, start: u64) -> Self::Frame;
+
+    fn dict(&mut self, start: u64) -> Self::Frame;
+
+    /// Sets the key the next value of a dictionary goes under.
+    fn key(&mut self, frame: &mut Self::Frame, key: Self::Key);
+
+    /// Adds an element to a list, or a value to a dictionary.
+    fn push(&mut self, frame: &mut Self::Frame, node: Self::Node);
+
+    fn end(&mut self, frame: Self::Frame, end: u64) -> Self::Node;
+}
+
+/// Keeps the containers being built on the heap instead of the call stack, so
+/// deeply nested input cannot overflow it.
+pub(crate) struct Builder<S: NodeSink> {
+    stack: Vec<S::Frame>,
+}
+
+impl<S: NodeSink> Builder<S> {
+    pub(crate) fn new() -> Builder<S> {
+        Builder { stack: vec![] }
+    }
+
+    /// Reads one item, returning the node once it's complete.
+    pub(crate) fn step<T>(
+        &mut self,
+        reader: &mut Reader,
+        lexer: &mut Lexer<'_, T>,
+        sink: &mut S,
+    ) -> Result<Option<S::Node>>
+    where
+        T: Iterator<Item = u8>,
+    {
+        let start = lexer.consumed;
+        let node = match reader.next_item(lexer, sink)? {
+            Item::Integer(value) => sink.integer(value, start..lexer.consumed),
+            #[cfg(feature = "bigint")]
+            Item::BigInteger(value) => sink.big_integer(value, start..lexer.consumed),
+            Item::Bytes(bytes) => sink.bytes(bytes, start..lexer.consumed),
+            Item::Key(key) => {
+                sink.key(self.stack.last_mut().unwrap(), key);
+                return Ok(None);
+            }
+            Item::StartList => {
+                self.stack.push(sink.list(start));
+                return Ok(None);
+            }
+            Item::StartDict => {
+                self.stack.push(sink.dict(start));
+                return Ok(None);
+            }
+            Item::EndList | Item::EndDict => {
+                let frame = self.stack.pop().unwrap();
+                sink.end(frame, lexer.consumed)
+            }
+        };
+
+        match self.stack.last_mut() {
+            None => Ok(Some(node)),
+            Some(frame) => {
+                sink.push(frame, node);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Reads a single node, leaving whatever follows it unconsumed.
+pub(crate) fn read_node<S, T>(
+    reader: &mut Reader,
+    lexer: &mut Lexer<'_, T>,
+    sink: &mut S,
+) -> Result<S::Node>
+where
+    S: NodeSink,
+    T: Iterator<Item = u8>,
+{
+    let mut builder = Builder::new();
+    loop {
+        if let Some(node) = builder.step(reader, lexer, sink)? {
+            return Ok(node);
+        }
+    }
+}
+
+/// Reads the only node of the input with the default [`ParserConfig`].
+pub(crate) fn read_single<S, T>(lexer: &mut Lexer<'_, T>, sink: &mut S) -> Result<S::Node>
+where
+    S: NodeSink,
+    T: Iterator<Item = u8>,
+{
+    let node = read_node(&mut Reader::new(ParserConfig::default()), lexer, sink)?;
+
+    match lexer.next_token()? {
+        Token::EOF => Ok(node),
+        found => throw!(unexpected("EOF", found), lexer.position()),
+    }
+}
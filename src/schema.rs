@@ -0,0 +1,288 @@
+//! Describing the expected shape of a node and checking nodes against it.
+
+use crate::prelude::*;
+use crate::{BNode, BNodeType, PathStep};
+use core::fmt::Display;
+
+/// The expected shape of a node.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BNodeSchema {
+    Integer,
+    Bytes,
+    /// A list of any length whose elements all match the schema.
+    List(Box<BNodeSchema>),
+    /// A list with exactly one element per schema, in order.
+    Tuple(Vec<BNodeSchema>),
+    /// A dictionary with the listed keys, any other key is allowed.
+    Dict(Vec<SchemaField>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SchemaField {
+    pub name: String,
+    pub schema: BNodeSchema,
+    pub required: bool,
+}
+
+impl SchemaField {
+    pub fn required(name: &str, schema: BNodeSchema) -> SchemaField {
+        SchemaField {
+            name: name.into(),
+            schema,
+            required: true,
+        }
+    }
+
+    pub fn optional(name: &str, schema: BNodeSchema) -> SchemaField {
+        SchemaField {
+            name: name.into(),
+            schema,
+            required: false,
+        }
+    }
+}
+
+/// A node which doesn't match its schema, found at `path` from the root.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SchemaError {
+    pub path: Vec<PathStep>,
+    pub message: String,
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "at root: {}", self.message);
+        }
+
+        f.write_str("at `")?;
+        for (i, step) in self.path.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            match step {
+                PathStep::Key(key) => f.write_str(key)?,
+                PathStep::Index(index) => write!(f, "{}", index)?,
+            }
+        }
+        write!(f, "`: {}", self.message)
+    }
+}
+
+impl core::error::Error for SchemaError {}
+
+fn schema_type(schema: &BNodeSchema) -> BNodeType {
+    match schema {
+        BNodeSchema::Integer => BNodeType::Integer,
+        BNodeSchema::Bytes => BNodeType::Bytes,
+        BNodeSchema::List(_) | BNodeSchema::Tuple(_) => BNodeType::List,
+        BNodeSchema::Dict(_) => BNodeType::Dict,
+    }
+}
+
+impl BNodeSchema {
+    /// Checks `node` against the schema, returning every mismatch found in
+    /// depth-first order, fields in the order of the schema, rather than
+    /// stopping at the first one.
+    pub fn validate(&self, node: &BNode) -> Result<(), Vec<SchemaError>> {
+        let mut errors = vec![];
+        // A missing required key is checked in turn with the other fields
+        // as `None`, so the errors come out in the order of the schema.
+        let mut stack = vec![(vec![], Some((self, node)))];
+
+        while let Some((path, entry)) = stack.pop() {
+            let Some((schema, node)) = entry else {
+                errors.push(SchemaError {
+                    path,
+                    message: "missing required key".into(),
+                });
+                continue;
+            };
+            let child = |step: PathStep| {
+                let mut path = path.clone();
+                path.push(step);
+                path
+            };
+
+            match (schema, node) {
+                (BNodeSchema::Integer, integer) if integer.is_integer() => {}
+                (BNodeSchema::Bytes, BNode::Bytes(_)) => {}
+                (BNodeSchema::List(schema), BNode::List(list)) => {
+                    stack.extend(
+                        list.iter()
+                            .enumerate()
+                            .rev()
+                            .map(|(i, node)| (child(PathStep::Index(i)), Some((&**schema, node)))),
+                    );
+                }
+                (BNodeSchema::Tuple(schemas), BNode::List(list)) => {
+                    if schemas.len() != list.len() {
+                        errors.push(SchemaError {
+                            message: format!(
+                                "expected {} elements, found {}",
+                                schemas.len(),
+                                list.len()
+                            ),
+                            path,
+                        });
+                        continue;
+                    }
+
+                    stack.extend(schemas.iter().zip(list).enumerate().rev().map(
+                        |(i, (schema, node))| (child(PathStep::Index(i)), Some((schema, node))),
+                    ));
+                }
+                (BNodeSchema::Dict(fields), dict) if dict.is_dict() => {
+                    stack.extend(fields.iter().rev().filter_map(|field| {
+                        let entry = match dict.get(&field.name) {
+                            Some(node) => Some((&field.schema, node)),
+                            None if field.required => None,
+                            None => return None,
+                        };
+                        Some((child(PathStep::Key(field.name.clone())), entry))
+                    }));
+                }
+                (schema, node) => errors.push(SchemaError {
+                    message: node.type_mismatch(schema_type(schema)),
+                    path,
+                }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// The schema of a BitTorrent metainfo file, for either a single file or a
+/// directory.
+pub fn torrent_schema() -> BNodeSchema {
+    let file = BNodeSchema::Dict(vec![
+        SchemaField::required("length", BNodeSchema::Integer),
+        SchemaField::required("path", BNodeSchema::List(Box::new(BNodeSchema::Bytes))),
+    ]);
+    let info = BNodeSchema::Dict(vec![
+        SchemaField::required("name", BNodeSchema::Bytes),
+        SchemaField::required("piece length", BNodeSchema::Integer),
+        SchemaField::required("pieces", BNodeSchema::Bytes),
+        SchemaField::optional("length", BNodeSchema::Integer),
+        SchemaField::optional("files", BNodeSchema::List(Box::new(file))),
+    ]);
+    let tiers = BNodeSchema::List(Box::new(BNodeSchema::List(Box::new(BNodeSchema::Bytes))));
+
+    BNodeSchema::Dict(vec![
+        SchemaField::required("announce", BNodeSchema::Bytes),
+        SchemaField::required("info", info),
+        SchemaField::optional("announce-list", tiers),
+        SchemaField::optional("creation date", BNodeSchema::Integer),
+        SchemaField::optional("comment", BNodeSchema::Bytes),
+        SchemaField::optional("httpseeds", BNodeSchema::List(Box::new(BNodeSchema::Bytes))),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{torrent_schema, BNodeSchema, SchemaError};
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{parse_from_str, BNode, PathStep};
+
+    #[test]
+    fn test_validate_torrent() {
+        let mut bnode = parse_from_str(DEBIAN_TORRENT).unwrap();
+        // The fixture's `info` dictionary is truncated before `pieces`.
+        bnode["info"]
+            .insert_entry("pieces".into(), BNode::Bytes(vec![0xab; 20]))
+            .unwrap();
+
+        assert_eq!(Ok(()), torrent_schema().validate(&bnode));
+    }
+
+    #[test]
+    fn test_validate_malformed_torrent() {
+        let bnode = parse_from_str(
+            "d8:announcei1e4:infod5:filesld6:length1:14:pathli1eeee4:namei1e6:pieces0:ee",
+        )
+        .unwrap();
+        let key = |k: &str| PathStep::Key(k.into());
+
+        let errors = torrent_schema().validate(&bnode).unwrap_err();
+        assert_eq!(
+            vec![
+                SchemaError {
+                    path: vec![key("announce")],
+                    message: "expected bytes, found integer".into(),
+                },
+                SchemaError {
+                    path: vec![key("info"), key("name")],
+                    message: "expected bytes, found integer".into(),
+                },
+                SchemaError {
+                    path: vec![key("info"), key("piece length")],
+                    message: "missing required key".into(),
+                },
+                SchemaError {
+                    path: vec![key("info"), key("files"), PathStep::Index(0), key("length")],
+                    message: "expected integer, found bytes".into(),
+                },
+                SchemaError {
+                    path: vec![
+                        key("info"),
+                        key("files"),
+                        PathStep::Index(0),
+                        key("path"),
+                        PathStep::Index(0)
+                    ],
+                    message: "expected bytes, found integer".into(),
+                },
+            ],
+            errors
+        );
+        assert_eq!(
+            "at `info/files/0/length`: expected integer, found bytes",
+            errors[3].to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate_tuple() {
+        let schema = BNodeSchema::Tuple(vec![BNodeSchema::Bytes, BNodeSchema::Integer]);
+
+        assert_eq!(
+            Ok(()),
+            schema.validate(&parse_from_str("l2:ipi1ee").unwrap())
+        );
+        assert_eq!(
+            Err(vec![SchemaError {
+                path: vec![],
+                message: "expected 2 elements, found 1".into(),
+            }]),
+            schema.validate(&parse_from_str("l2:ipe").unwrap())
+        );
+        assert_eq!(
+            "at root: expected list, found integer",
+            schema.validate(&BNode::Integer(1)).unwrap_err()[0].to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_validate_ordered_torrent() {
+        let mut bnode = crate::parse_ordered(&mut DEBIAN_TORRENT.bytes()).unwrap();
+        bnode["info"]
+            .insert_entry("pieces".into(), BNode::Bytes(vec![0xab; 20]))
+            .unwrap();
+
+        assert_eq!(Ok(()), torrent_schema().validate(&bnode));
+    }
+
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_validate_big_integer() {
+        let bnode = parse_from_str("i99999999999999999999e").unwrap();
+
+        assert_eq!(Ok(()), BNodeSchema::Integer.validate(&bnode));
+    }
+}
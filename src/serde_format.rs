@@ -0,0 +1,603 @@
+//! A serde data format backed by bencode.
+//!
+//! Values are converted through the [`BNode`] tree: integers map to
+//! `visit_i64`, byte strings to `visit_bytes`, lists to `visit_seq` and
+//! dictionaries to `visit_map`. `bool` is encoded as `i1e`/`i0e` and `None`
+//! fields are omitted from dictionaries, since bencode has no null.
+
+use crate::{parse_from_slice, BDict, BList, BNode, Error, ErrorKind, Result};
+use core::fmt::Display;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize};
+
+pub fn from_bencode<T: DeserializeOwned>(input: &[u8]) -> Result<T> {
+    from_bnode(parse_from_slice(input)?)
+}
+
+pub fn to_bencode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    Ok(to_bnode(value)?.to_bytes())
+}
+
+pub fn from_bnode<T: DeserializeOwned>(node: BNode) -> Result<T> {
+    T::deserialize(Deserializer { node })
+}
+
+pub fn to_bnode<T: Serialize>(value: &T) -> Result<BNode> {
+    match value.serialize(Serializer)? {
+        Some(node) => Ok(node),
+        None => Err(custom("cannot encode an empty value")),
+    }
+}
+
+fn custom<T: Display>(msg: T) -> Error {
+    // Errors raised by serde are not tied to a position in the input.
+    Error::new(ErrorKind::Custom(msg.to_string()), Error::NO_POSITION)
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        custom(msg)
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        custom(msg)
+    }
+}
+
+struct Deserializer {
+    node: BNode,
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.node {
+            BNode::Integer(i) => visitor.visit_i64(i),
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(i) => Err(custom(format!("integer {} is out of range", i))),
+            BNode::Bytes(bytes) => visitor.visit_bytes(&bytes),
+            BNode::List(list) => visitor.visit_seq(ListAccess {
+                iter: list.into_iter(),
+            }),
+            BNode::Dict(dict) => visitor.visit_map(DictAccess {
+                iter: dict.into_iter(),
+                value: None,
+            }),
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => visitor.visit_map(DictAccess {
+                iter: dict.into_iter().collect::<BDict>().into_iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.node {
+            BNode::Integer(i) => visitor.visit_bool(i != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.node {
+            BNode::Bytes(bytes) => match String::from_utf8(bytes) {
+                Ok(string) => visitor.visit_string(string),
+                Err(e) => visitor.visit_byte_buf(e.into_bytes()),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.node {
+            BNode::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.node {
+            BNode::Bytes(_) => visitor.visit_enum(Enum {
+                variant: self.node,
+                value: None,
+            }),
+            BNode::Dict(dict) if dict.len() == 1 => {
+                let (variant, value) = dict.into_iter().next().unwrap();
+                visitor.visit_enum(Enum {
+                    variant: variant.into(),
+                    value: Some(value),
+                })
+            }
+            _ => Err(custom(
+                "expected a byte string or a single-entry dictionary",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 bytes seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct ListAccess {
+    iter: alloc::vec::IntoIter<BNode>,
+}
+
+impl<'de> SeqAccess<'de> for ListAccess {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        match self.iter.next() {
+            Some(node) => seed.deserialize(Deserializer { node }).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct DictAccess {
+    iter: alloc::collections::btree_map::IntoIter<String, BNode>,
+    value: Option<BNode>,
+}
+
+impl<'de> MapAccess<'de> for DictAccess {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        match self.value.take() {
+            Some(node) => seed.deserialize(Deserializer { node }),
+            None => Err(custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct Enum {
+    variant: BNode,
+    value: Option<BNode>,
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(mut self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = core::mem::replace(&mut self.variant, BNode::Integer(0));
+        let value = seed.deserialize(Deserializer { node: variant })?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Enum {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.value {
+            None => Ok(()),
+            Some(_) => Err(custom("expected a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        match self.value {
+            Some(node) => seed.deserialize(Deserializer { node }),
+            None => Err(custom("expected a newtype variant")),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        match self.value {
+            Some(node) => de::Deserializer::deserialize_seq(Deserializer { node }, visitor),
+            None => Err(custom("expected a tuple variant")),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.value {
+            Some(node) => de::Deserializer::deserialize_map(Deserializer { node }, visitor),
+            None => Err(custom("expected a struct variant")),
+        }
+    }
+}
+
+/// Serializes into a [`BNode`], `None` is produced for values which have no
+/// bencode representation and are skipped inside dictionaries.
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Option<BNode>;
+    type Error = Error;
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = VariantSerializer<ListSerializer>;
+    type SerializeMap = DictSerializer;
+    type SerializeStruct = DictSerializer;
+    type SerializeStructVariant = VariantSerializer<DictSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        Ok(Some(BNode::Integer(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        match i64::try_from(v) {
+            Ok(v) => self.serialize_i64(v),
+            Err(_) => Err(custom(format!("integer {} is out of range", v))),
+        }
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
+        Err(custom("floating point numbers are not supported"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok> {
+        Err(custom("floating point numbers are not supported"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        Ok(Some(v.into()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        Ok(Some(v.into()))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        let mut dict = BDict::new();
+        if let Some(node) = value.serialize(Serializer)? {
+            dict.insert(variant.to_string(), node);
+        }
+        Ok(Some(dict.into()))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(ListSerializer {
+            list: BList::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(DictSerializer {
+            dict: BDict::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+struct ListSerializer {
+    list: BList,
+}
+
+impl ListSerializer {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        match value.serialize(Serializer)? {
+            Some(node) => {
+                self.list.push(node);
+                Ok(())
+            }
+            None => Err(custom("cannot encode an empty value in a list")),
+        }
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = Option<BNode>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.list.into()))
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Option<BNode>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.list.into()))
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Option<BNode>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.list.into()))
+    }
+}
+
+struct DictSerializer {
+    dict: BDict,
+    key: Option<String>,
+}
+
+impl DictSerializer {
+    fn insert<T: ?Sized + Serialize>(&mut self, key: String, value: &T) -> Result<()> {
+        if let Some(node) = value.serialize(Serializer)? {
+            self.dict.insert(key, node);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for DictSerializer {
+    type Ok = Option<BNode>;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        match key.serialize(Serializer)? {
+            Some(BNode::Bytes(bytes)) => match String::from_utf8(bytes) {
+                Ok(key) => {
+                    self.key = Some(key);
+                    Ok(())
+                }
+                Err(_) => Err(custom("dictionary key must be valid UTF-8")),
+            },
+            _ => Err(custom("dictionary key must be a string")),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        match self.key.take() {
+            Some(key) => self.insert(key, value),
+            None => Err(custom("serialize_value called before serialize_key")),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.dict.into()))
+    }
+}
+
+impl ser::SerializeStruct for DictSerializer {
+    type Ok = Option<BNode>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.dict.into()))
+    }
+}
+
+/// Wraps the content of an enum variant into a single-entry dictionary.
+struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> VariantSerializer<S> {
+    fn wrap(variant: &str, node: Option<BNode>) -> Result<Option<BNode>> {
+        let mut dict = BDict::new();
+        if let Some(node) = node {
+            dict.insert(variant.to_string(), node);
+        }
+        Ok(Some(dict.into()))
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<ListSerializer> {
+    type Ok = Option<BNode>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Self::wrap(self.variant, ser::SerializeSeq::end(self.inner)?)
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<DictSerializer> {
+    type Ok = Option<BNode>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.inner.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Self::wrap(self.variant, ser::SerializeMap::end(self.inner)?)
+    }
+}
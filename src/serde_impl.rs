@@ -0,0 +1,106 @@
+use crate::{BDict, BList, BNode};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+impl Serialize for BNode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            BNode::Integer(i) => serializer.serialize_i64(*i),
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(i) => Err(serde::ser::Error::custom(format!(
+                "integer {} is out of range",
+                i
+            ))),
+            BNode::Bytes(bytes) => serde_bytes::Bytes::new(bytes).serialize(serializer),
+            BNode::List(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for node in list {
+                    seq.serialize_element(node)?;
+                }
+                seq.end()
+            }
+            BNode::Dict(dict) => {
+                let mut map = serializer.serialize_map(Some(dict.len()))?;
+                for (key, value) in dict {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(dict) => {
+                let mut map = serializer.serialize_map(Some(dict.len()))?;
+                for (key, value) in dict {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BNode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(BNodeVisitor)
+    }
+}
+
+struct BNodeVisitor;
+
+impl<'de> Visitor<'de> for BNodeVisitor {
+    type Value = BNode;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("an integer, byte string, list or dictionary")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(BNode::Integer(v as i64))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(BNode::Integer(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => Ok(BNode::Integer(v)),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        // Other formats may take the hint from the input, so it can't be
+        // trusted to allocate everything upfront.
+        let mut list = BList::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(node) = seq.next_element()? {
+            list.push(node);
+        }
+        Ok(list.into())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut dict = BDict::new();
+        while let Some((key, value)) = map.next_entry()? {
+            dict.insert(key, value);
+        }
+        Ok(dict.into())
+    }
+}
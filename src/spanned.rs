@@ -0,0 +1,216 @@
+use crate::prelude::*;
+use crate::reader::{read_single, NodeSink, Strings};
+use crate::{BDict, BList, BNode, Lexer, PathStep, Result};
+use core::ops::Range;
+
+/// A parsed node along with the byte range it was parsed from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BNodeSpanned {
+    pub node: BNode,
+    /// Offsets into the input, from the first byte of the node to one past
+    /// its last byte.
+    pub span: Range<u64>,
+    /// The path and span of every node below the root, in the order of the
+    /// input.
+    pub spans: Vec<(Vec<PathStep>, Range<u64>)>,
+}
+
+impl BNodeSpanned {
+    /// The span of the node at `path`, the root for an empty one. A repeated
+    /// dictionary key gives the span of its last value, the one kept.
+    pub fn span_of(&self, path: &[PathStep]) -> Option<Range<u64>> {
+        if path.is_empty() {
+            return Some(self.span.clone());
+        }
+
+        self.spans
+            .iter()
+            .rfind(|(steps, _)| steps == path)
+            .map(|(_, span)| span.clone())
+    }
+}
+
+/// A container being parsed along with the index of its entry in the spans.
+enum SpannedFrame {
+    List(BList, usize),
+    Dict(BDict, Option<String>, usize),
+}
+
+/// Builds the node once while recording the spans on the side, each node
+/// under the path kept up to date in `path`.
+#[derive(Default)]
+struct SpannedSink {
+    path: Vec<PathStep>,
+    spans: Vec<(Vec<PathStep>, Range<u64>)>,
+}
+
+impl SpannedSink {
+    fn record(&mut self, span: Range<u64>) -> usize {
+        self.spans.push((self.path.clone(), span));
+        self.spans.len() - 1
+    }
+}
+
+impl Strings for SpannedSink {
+    type Bytes = Vec<u8>;
+    type Key = String;
+
+    fn read_bytes<T>(&mut self, lexer: &mut Lexer<'_, T>, len: usize) -> Result<Vec<u8>>
+    where
+        T: Iterator<Item = u8>,
+    {
+        lexer.read_bytes(len)
+    }
+
+    fn make_key(&mut self, bytes: Vec<u8>) -> Option<String> {
+        String::from_utf8(bytes).ok()
+    }
+}
+
+impl NodeSink for SpannedSink {
+    type Node = BNode;
+    type Frame = SpannedFrame;
+
+    fn integer(&mut self, value: i64, span: Range<u64>) -> BNode {
+        self.record(span);
+        BNode::Integer(value)
+    }
+
+    #[cfg(feature = "bigint")]
+    fn big_integer(&mut self, value: Box<num_bigint::BigInt>, span: Range<u64>) -> BNode {
+        self.record(span);
+        BNode::BigInteger(value)
+    }
+
+    fn bytes(&mut self, bytes: Vec<u8>, span: Range<u64>) -> BNode {
+        self.record(span);
+        BNode::Bytes(bytes)
+    }
+
+    fn list(&mut self, start: u64) -> SpannedFrame {
+        let index = self.record(start..start);
+        self.path.push(PathStep::Index(0));
+        SpannedFrame::List(BList::new(), index)
+    }
+
+    fn dict(&mut self, start: u64) -> SpannedFrame {
+        let index = self.record(start..start);
+        self.path.push(PathStep::Key(String::new()));
+        SpannedFrame::Dict(BDict::new(), None, index)
+    }
+
+    fn key(&mut self, frame: &mut SpannedFrame, key: String) {
+        if let SpannedFrame::Dict(_, pending, _) = frame {
+            *self.path.last_mut().unwrap() = PathStep::Key(key.clone());
+            *pending = Some(key);
+        }
+    }
+
+    fn push(&mut self, frame: &mut SpannedFrame, node: BNode) {
+        match frame {
+            SpannedFrame::List(list, _) => {
+                list.push(node);
+                *self.path.last_mut().unwrap() = PathStep::Index(list.len());
+            }
+            SpannedFrame::Dict(dict, key, _) => {
+                dict.insert(key.take().unwrap(), node);
+            }
+        }
+    }
+
+    fn end(&mut self, frame: SpannedFrame, end: u64) -> BNode {
+        self.path.pop();
+        let (node, index) = match frame {
+            SpannedFrame::List(list, index) => (BNode::List(list), index),
+            SpannedFrame::Dict(dict, _, index) => (BNode::Dict(dict), index),
+        };
+        self.spans[index].1.end = end;
+        node
+    }
+}
+
+/// Parses `input` like [`parse_from_slice`](crate::parse_from_slice),
+/// recording where every node was found.
+pub fn parse_with_spans(input: &[u8]) -> Result<BNodeSpanned> {
+    let mut stream = input.iter().copied();
+    let mut sink = SpannedSink::default();
+    let node = read_single(&mut Lexer::new(&mut stream), &mut sink)?;
+
+    // The root comes first.
+    let mut spans = sink.spans;
+    let (_, span) = spans.remove(0);
+    Ok(BNodeSpanned { node, span, spans })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_with_spans;
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{parse_from_slice, BNode, PathStep};
+
+    #[test]
+    fn test_parse_with_spans() {
+        let spanned = parse_with_spans(b"i42e").unwrap();
+        assert_eq!(BNode::Integer(42), spanned.node);
+        assert_eq!(0..4, spanned.span);
+        assert!(spanned.spans.is_empty());
+
+        let raw = b"d1:ali1e4:spame1:bdee";
+        let spanned = parse_with_spans(raw).unwrap();
+        assert_eq!(0..raw.len() as u64, spanned.span);
+        assert_eq!(parse_from_slice(raw).unwrap(), spanned.node);
+
+        let a = PathStep::Key("a".to_string());
+        let b = PathStep::Key("b".to_string());
+        assert_eq!(Some(0..21), spanned.span_of(&[]));
+        assert_eq!(Some(4..15), spanned.span_of(core::slice::from_ref(&a)));
+        assert_eq!(
+            Some(5..8),
+            spanned.span_of(&[a.clone(), PathStep::Index(0)])
+        );
+        assert_eq!(Some(8..14), spanned.span_of(&[a, PathStep::Index(1)]));
+        assert_eq!(Some(18..20), spanned.span_of(&[b]));
+        assert_eq!(None, spanned.span_of(&[PathStep::Index(0)]));
+    }
+
+    #[test]
+    fn test_parse_with_spans_slices() {
+        let raw = DEBIAN_TORRENT.as_bytes();
+        let spanned = parse_with_spans(raw).unwrap();
+        assert_eq!(parse_from_slice(raw).unwrap(), spanned.node);
+        assert_eq!(spanned.node.count_nodes() - 1, spanned.spans.len());
+
+        for (path, span) in &spanned.spans {
+            let span = span.start as usize..span.end as usize;
+            assert_eq!(spanned.node.get_path(path).unwrap().to_bytes(), &raw[span]);
+        }
+    }
+
+    #[test]
+    fn test_parse_with_spans_repeated_key() {
+        let spanned = parse_with_spans(b"d1:ai1e1:ai22ee").unwrap();
+        assert_eq!(2, spanned.spans.len());
+        assert_eq!(
+            Some(10..14),
+            spanned.span_of(&[PathStep::Key("a".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_parse_with_spans_failed() {
+        let cases: [&[u8]; 6] = [
+            b"",
+            b"5:halo",
+            b"i1ei2e",
+            b"l4:halo",
+            b"d1:\xffi1ee",
+            b"di1ei2ee",
+        ];
+        for case in cases {
+            let err = parse_with_spans(case).unwrap_err();
+            let expected = parse_from_slice(case).unwrap_err();
+            assert_eq!(expected.kind(), err.kind());
+            assert_eq!(expected.position, err.position);
+        }
+    }
+}
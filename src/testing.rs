@@ -0,0 +1,45 @@
+//! [proptest] strategies generating arbitrary nodes.
+
+use crate::BNode;
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+
+/// Upper bound of elements generated for a single list or dictionary.
+const MAX_ITEMS: usize = 8;
+
+pub fn prop_bnode_integer() -> impl Strategy<Value = BNode> {
+    any::<i64>().prop_map(BNode::Integer)
+}
+
+pub fn prop_bnode_bytes() -> impl Strategy<Value = BNode> {
+    vec(any::<u8>(), 0..32).prop_map(BNode::Bytes)
+}
+
+/// Generates any node whose [`BNode::depth`] is at most `max_depth`.
+pub fn prop_bnode(max_depth: usize) -> impl Strategy<Value = BNode> {
+    let leaf = prop_oneof![prop_bnode_integer(), prop_bnode_bytes()];
+
+    leaf.prop_recursive(max_depth as u32, 64, MAX_ITEMS as u32, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..MAX_ITEMS).prop_map(BNode::List),
+            btree_map(any::<String>(), inner, 0..MAX_ITEMS).prop_map(BNode::Dict),
+        ]
+    })
+}
+
+/// Generates a list whose [`BNode::depth`] is at most `max_depth`, which
+/// must be at least `1`.
+pub fn prop_bnode_list(max_depth: usize) -> impl Strategy<Value = BNode> {
+    vec(prop_bnode(max_depth.saturating_sub(1)), 0..MAX_ITEMS).prop_map(BNode::List)
+}
+
+/// Generates a dictionary whose [`BNode::depth`] is at most `max_depth`,
+/// which must be at least `1`.
+pub fn prop_bnode_dict(max_depth: usize) -> impl Strategy<Value = BNode> {
+    btree_map(
+        any::<String>(),
+        prop_bnode(max_depth.saturating_sub(1)),
+        0..MAX_ITEMS,
+    )
+    .prop_map(BNode::Dict)
+}
@@ -0,0 +1,559 @@
+//! Helpers for BitTorrent metainfo files.
+
+use crate::prelude::*;
+use crate::{BDict, BList, BNode, BNodeType};
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// A metainfo file, keys which are not listed here are dropped.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Torrent {
+    pub announce: String,
+    pub info: TorrentInfo,
+    /// `creation date`, in seconds since the Unix epoch.
+    pub creation_date: Option<i64>,
+    pub comment: Option<String>,
+}
+
+/// The `info` dictionary, describing a single file if `length` is set or a
+/// directory of `files` otherwise.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TorrentInfo {
+    pub name: String,
+    /// `piece length`
+    pub piece_length: i64,
+    /// The concatenated 20-byte SHA-1 hashes of all pieces.
+    pub pieces: Vec<u8>,
+    pub length: Option<i64>,
+    pub files: Option<Vec<FileEntry>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FileEntry {
+    pub length: i64,
+    /// The path components relative to the torrent's `name`.
+    pub path: Vec<String>,
+}
+
+fn take<T>(dict: &mut BDict, key: &str) -> Result<T, String>
+where
+    T: TryFrom<BNode, Error = String>,
+{
+    match take_optional(dict, key)? {
+        Some(value) => Ok(value),
+        None => Err(format!("missing key `{}`", key)),
+    }
+}
+
+fn take_optional<T>(dict: &mut BDict, key: &str) -> Result<Option<T>, String>
+where
+    T: TryFrom<BNode, Error = String>,
+{
+    dict.remove(key)
+        .map(|value| T::try_from(value).map_err(|e| format!("field `{}`: {}", key, e)))
+        .transpose()
+}
+
+fn list_of<T>(node: BNode) -> Result<Vec<T>, String>
+where
+    T: TryFrom<BNode, Error = String>,
+{
+    BList::try_from(node)?
+        .into_iter()
+        .map(T::try_from)
+        .collect()
+}
+
+impl TryFrom<BNode> for FileEntry {
+    type Error = String;
+
+    fn try_from(node: BNode) -> Result<Self, Self::Error> {
+        let mut dict = BDict::try_from(node)?;
+
+        Ok(FileEntry {
+            length: take(&mut dict, "length")?,
+            path: dict
+                .remove("path")
+                .map(list_of)
+                .ok_or("missing key `path`")?
+                .map_err(|e| format!("field `path`: {}", e))?,
+        })
+    }
+}
+
+impl TryFrom<BNode> for TorrentInfo {
+    type Error = String;
+
+    fn try_from(node: BNode) -> Result<Self, Self::Error> {
+        let mut dict = BDict::try_from(node)?;
+
+        Ok(TorrentInfo {
+            name: take(&mut dict, "name")?,
+            piece_length: take(&mut dict, "piece length")?,
+            pieces: take(&mut dict, "pieces")?,
+            length: take_optional(&mut dict, "length")?,
+            files: dict
+                .remove("files")
+                .map(list_of)
+                .transpose()
+                .map_err(|e| format!("field `files`: {}", e))?,
+        })
+    }
+}
+
+impl TryFrom<BNode> for Torrent {
+    type Error = String;
+
+    fn try_from(node: BNode) -> Result<Self, Self::Error> {
+        let mut dict = BDict::try_from(node)?;
+
+        Ok(Torrent {
+            announce: take(&mut dict, "announce")?,
+            info: take(&mut dict, "info")?,
+            creation_date: take_optional(&mut dict, "creation date")?,
+            comment: take_optional(&mut dict, "comment")?,
+        })
+    }
+}
+
+impl From<FileEntry> for BNode {
+    fn from(value: FileEntry) -> Self {
+        let mut dict = BDict::new();
+        dict.insert("length".into(), value.length.into());
+        dict.insert(
+            "path".into(),
+            value.path.into_iter().map(BNode::from).collect(),
+        );
+        dict.into()
+    }
+}
+
+impl From<TorrentInfo> for BNode {
+    fn from(value: TorrentInfo) -> Self {
+        let mut dict = BDict::new();
+        dict.insert("name".into(), value.name.into());
+        dict.insert("piece length".into(), value.piece_length.into());
+        dict.insert("pieces".into(), value.pieces.into());
+        if let Some(length) = value.length {
+            dict.insert("length".into(), length.into());
+        }
+        if let Some(files) = value.files {
+            dict.insert("files".into(), files.into_iter().map(BNode::from).collect());
+        }
+        dict.into()
+    }
+}
+
+impl From<Torrent> for BNode {
+    fn from(value: Torrent) -> Self {
+        let mut dict = BDict::new();
+        dict.insert("announce".into(), value.announce.into());
+        dict.insert("info".into(), value.info.into());
+        if let Some(creation_date) = value.creation_date {
+            dict.insert("creation date".into(), creation_date.into());
+        }
+        if let Some(comment) = value.comment {
+            dict.insert("comment".into(), comment.into());
+        }
+        dict.into()
+    }
+}
+
+fn check_file_entry(entry: &BNode, at: &str, violations: &mut Vec<String>) {
+    if !entry.is_dict() {
+        return violations.push(format!("`{}` is not a dictionary", at));
+    }
+
+    match entry.get("length") {
+        None => violations.push(format!("missing key `{}.length`", at)),
+        Some(BNode::Integer(length)) if *length >= 0 => {}
+        Some(_) => violations.push(format!("`{}.length` is not a non-negative integer", at)),
+    }
+    match entry.get("path") {
+        None => violations.push(format!("missing key `{}.path`", at)),
+        Some(BNode::List(path)) if !path.is_empty() && path.iter().all(BNode::is_bytes) => {}
+        Some(_) => violations.push(format!(
+            "`{}.path` is not a non-empty list of byte strings",
+            at
+        )),
+    }
+}
+
+fn check_info(info: &BNode, violations: &mut Vec<String>) {
+    if !info.is_dict() {
+        return violations.push("`info` is not a dictionary".into());
+    }
+
+    match info.get("name") {
+        None => violations.push("missing key `info.name`".into()),
+        Some(name) if !name.is_bytes() => {
+            violations.push("`info.name` is not a byte string".into())
+        }
+        Some(_) => {}
+    }
+    match info.get("piece length") {
+        None => violations.push("missing key `info.piece length`".into()),
+        Some(BNode::Integer(len)) if *len > 0 => {}
+        Some(_) => violations.push("`info.piece length` is not a positive integer".into()),
+    }
+    match info.get("pieces") {
+        None => violations.push("missing key `info.pieces`".into()),
+        Some(BNode::Bytes(pieces)) if pieces.len() % 20 == 0 => {}
+        Some(BNode::Bytes(_)) => {
+            violations.push("`info.pieces` length is not a multiple of 20".into())
+        }
+        Some(_) => violations.push("`info.pieces` is not a byte string".into()),
+    }
+
+    match (info.get("length"), info.get("files")) {
+        (None, None) => violations.push("missing key `info.length` or `info.files`".into()),
+        (Some(_), Some(_)) => {
+            violations.push("both `info.length` and `info.files` are present".into())
+        }
+        (Some(BNode::Integer(length)), None) if *length >= 0 => {}
+        (Some(_), None) => violations.push("`info.length` is not a non-negative integer".into()),
+        (None, Some(BNode::List(files))) => {
+            for (i, entry) in files.iter().enumerate() {
+                check_file_entry(entry, &format!("info.files[{}]", i), violations);
+            }
+        }
+        (None, Some(_)) => violations.push("`info.files` is not a list".into()),
+    }
+}
+
+impl BNode {
+    /// Checks the node is a valid metainfo file, returning every violation
+    /// found rather than stopping at the first one.
+    pub fn verify_torrent(&self) -> Result<(), Vec<String>> {
+        if !self.is_dict() {
+            return Err(vec![self.type_mismatch(BNodeType::Dict)]);
+        }
+        let mut violations = vec![];
+
+        match self.get("announce") {
+            None => violations.push("missing key `announce`".into()),
+            Some(announce) if announce.as_str().is_err() => {
+                violations.push("`announce` is not a UTF-8 string".into())
+            }
+            Some(_) => {}
+        }
+        match self.get("info") {
+            None => violations.push("missing key `info`".into()),
+            Some(info) => check_info(info, &mut violations),
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// Splits packed peer records of `N` bytes, an IP address followed by a
+/// big-endian port.
+fn compact_records<const N: usize>(node: &BNode) -> Result<Vec<([u8; N], u16)>, String> {
+    let bytes = node.as_bytes()?;
+    if bytes.len() % (N + 2) != 0 {
+        return Err(format!("length is not a multiple of {}", N + 2));
+    }
+
+    Ok(bytes
+        .chunks_exact(N + 2)
+        .map(|record| {
+            let (ip, port) = record.split_at(N);
+            (
+                ip.try_into().unwrap(),
+                u16::from_be_bytes([port[0], port[1]]),
+            )
+        })
+        .collect())
+}
+
+impl BNode {
+    /// Reads the peers of a tracker response in the compact format, 6 bytes
+    /// per peer.
+    pub fn parse_compact_peers(&self) -> Result<Vec<(Ipv4Addr, u16)>, String> {
+        Ok(compact_records::<4>(self)?
+            .into_iter()
+            .map(|(ip, port)| (Ipv4Addr::from(ip), port))
+            .collect())
+    }
+
+    /// Like [`BNode::parse_compact_peers`] for the `peers6` key, 18 bytes per
+    /// peer.
+    pub fn parse_compact_peers_v6(&self) -> Result<Vec<(Ipv6Addr, u16)>, String> {
+        Ok(compact_records::<16>(self)?
+            .into_iter()
+            .map(|(ip, port)| (Ipv6Addr::from(ip), port))
+            .collect())
+    }
+
+    pub fn from_compact_peers(peers: &[(Ipv4Addr, u16)]) -> BNode {
+        let mut bytes = Vec::with_capacity(peers.len() * 6);
+        for (ip, port) in peers {
+            bytes.extend_from_slice(&ip.octets());
+            bytes.extend_from_slice(&port.to_be_bytes());
+        }
+
+        BNode::Bytes(bytes)
+    }
+}
+
+/// Returns the SHA-1 digest of the canonical encoding of `torrent["info"]`.
+pub fn info_hash(torrent: &BNode) -> Result<[u8; 20], String> {
+    if !torrent.is_dict() {
+        return Err(torrent.type_mismatch(BNodeType::Dict));
+    }
+    let info = torrent.get("info").ok_or("missing key `info`")?;
+    if !info.is_dict() {
+        return Err("`info` is not a dictionary".into());
+    }
+
+    Ok(info.sha1_bytes())
+}
+
+/// Like [`info_hash`], formatted as lowercase hex.
+pub fn info_hash_hex(torrent: &BNode) -> Result<String, String> {
+    Ok(info_hash(torrent)?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{info_hash, info_hash_hex, FileEntry, Torrent, TorrentInfo};
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{parse_from_str, BNode};
+    use core::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_torrent_from_bnode() {
+        let mut bnode = parse_from_str(DEBIAN_TORRENT).unwrap();
+        // The fixture's `info` dictionary is truncated before `pieces`.
+        bnode["info"]
+            .insert_entry("pieces".into(), BNode::Bytes(vec![0xab; 20]))
+            .unwrap();
+
+        let torrent = Torrent::try_from(bnode).unwrap();
+        assert_eq!(
+            "http://bttracker.debian.org:6969/announce",
+            torrent.announce
+        );
+        assert_eq!(Some(1573903810), torrent.creation_date);
+        assert_eq!(
+            Some("\"Debian CD from cdimage.debian.org\"".to_string()),
+            torrent.comment
+        );
+        assert_eq!(
+            TorrentInfo {
+                name: "debian-10.2.0-amd64-netinst.iso".into(),
+                piece_length: 262144,
+                pieces: vec![0xab; 20],
+                length: Some(351272960),
+                files: None,
+            },
+            torrent.info
+        );
+    }
+
+    #[test]
+    fn test_torrent_round_trip() {
+        let torrent = Torrent {
+            announce: "udp://tracker".into(),
+            info: TorrentInfo {
+                name: "dir".into(),
+                piece_length: 16384,
+                pieces: vec![0; 20],
+                length: None,
+                files: Some(vec![FileEntry {
+                    length: 3,
+                    path: vec!["a".into(), "b.txt".into()],
+                }]),
+            },
+            creation_date: None,
+            comment: None,
+        };
+
+        let bnode = BNode::from(torrent.clone());
+        assert!(!bnode.contains_key("comment"));
+        assert_eq!(Ok(torrent), Torrent::try_from(bnode));
+    }
+
+    #[test]
+    fn test_torrent_from_bnode_failed() {
+        let err = Torrent::try_from(parse_from_str("d8:announce1:ae").unwrap());
+        assert_eq!(Err("missing key `info`".to_string()), err);
+
+        let err = TorrentInfo::try_from(
+            parse_from_str("d4:name1:a12:piece lengthi1e6:pieces0:5:filesli1eee").unwrap(),
+        );
+        assert_eq!(
+            Err("field `files`: expected dict, found integer".to_string()),
+            err
+        );
+    }
+
+    #[test]
+    fn test_verify_torrent() {
+        let mut bnode = parse_from_str(DEBIAN_TORRENT).unwrap();
+        assert_eq!(
+            Err(vec!["missing key `info.pieces`".to_string()]),
+            bnode.verify_torrent()
+        );
+
+        bnode["info"]
+            .insert_entry("pieces".into(), BNode::Bytes(vec![0xab; 40]))
+            .unwrap();
+        assert_eq!(Ok(()), bnode.verify_torrent());
+
+        let multi_file = BNode::from(Torrent {
+            announce: "udp://tracker".into(),
+            info: TorrentInfo {
+                name: "dir".into(),
+                piece_length: 16384,
+                pieces: vec![0; 20],
+                length: None,
+                files: Some(vec![FileEntry {
+                    length: 3,
+                    path: vec!["a".into(), "b.txt".into()],
+                }]),
+            },
+            creation_date: None,
+            comment: None,
+        });
+        assert_eq!(Ok(()), multi_file.verify_torrent());
+    }
+
+    #[test]
+    fn test_verify_torrent_failed() {
+        assert_eq!(
+            Err(vec!["expected dict, found integer".to_string()]),
+            BNode::Integer(1).verify_torrent()
+        );
+        assert_eq!(
+            Err(vec![
+                "missing key `announce`".to_string(),
+                "missing key `info`".to_string()
+            ]),
+            parse_from_str("de").unwrap().verify_torrent()
+        );
+
+        let bnode = parse_from_str(
+            "d8:announcei1e4:infod6:lengthi1e4:namei1e12:piece lengthi0e6:pieces3:abcee",
+        )
+        .unwrap();
+        assert_eq!(
+            Err(vec![
+                "`announce` is not a UTF-8 string".to_string(),
+                "`info.name` is not a byte string".to_string(),
+                "`info.piece length` is not a positive integer".to_string(),
+                "`info.pieces` length is not a multiple of 20".to_string(),
+            ]),
+            bnode.verify_torrent()
+        );
+
+        let mut bnode = parse_from_str(
+            "d8:announce1:a4:infod5:filesld6:lengthi-1e4:pathleei1ee4:name1:a12:piece lengthi1e6:pieces0:ee",
+        )
+        .unwrap();
+        assert_eq!(
+            Err(vec![
+                "`info.files[0].length` is not a non-negative integer".to_string(),
+                "`info.files[0].path` is not a non-empty list of byte strings".to_string(),
+                "`info.files[1]` is not a dictionary".to_string(),
+            ]),
+            bnode.verify_torrent()
+        );
+
+        bnode["announce"] = BNode::Bytes(vec![0xff]);
+        bnode["info"]
+            .insert_entry("length".into(), 1.into())
+            .unwrap();
+        assert_eq!(
+            Err(vec![
+                "`announce` is not a UTF-8 string".to_string(),
+                "both `info.length` and `info.files` are present".to_string(),
+            ]),
+            bnode.verify_torrent()
+        );
+    }
+
+    #[test]
+    fn test_compact_peers() {
+        let bnode = BNode::Bytes(vec![10, 0, 0, 1, 0x1a, 0xe1, 192, 168, 1, 2, 0, 80]);
+        let peers = vec![
+            (Ipv4Addr::new(10, 0, 0, 1), 6881),
+            (Ipv4Addr::new(192, 168, 1, 2), 80),
+        ];
+
+        assert_eq!(Ok(peers.clone()), bnode.parse_compact_peers());
+        assert_eq!(bnode, BNode::from_compact_peers(&peers));
+        assert_eq!(BNode::Bytes(vec![]), BNode::from_compact_peers(&[]));
+        assert_eq!(Ok(vec![]), BNode::Bytes(vec![]).parse_compact_peers());
+
+        let mut raw = Ipv6Addr::LOCALHOST.octets().to_vec();
+        raw.extend_from_slice(&[0x1a, 0xe1]);
+        assert_eq!(
+            Ok(vec![(Ipv6Addr::LOCALHOST, 6881)]),
+            BNode::Bytes(raw).parse_compact_peers_v6()
+        );
+    }
+
+    #[test]
+    fn test_compact_peers_failed() {
+        assert_eq!(
+            Err("expected bytes, found integer".to_string()),
+            BNode::Integer(1).parse_compact_peers()
+        );
+        assert_eq!(
+            Err("length is not a multiple of 6".to_string()),
+            BNode::Bytes(vec![0; 7]).parse_compact_peers()
+        );
+        assert_eq!(
+            Err("length is not a multiple of 18".to_string()),
+            BNode::Bytes(vec![0; 6]).parse_compact_peers_v6()
+        );
+    }
+
+    #[test]
+    fn test_info_hash() {
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+
+        // The fixture's `info` dictionary lacks `pieces`, so this differs from
+        // the published info hash of debian-10.2.0-amd64-netinst.iso.
+        assert_eq!(
+            Ok("d145f0abcb15710d4cb7d2b2faa3b4b823aca9fe".to_string()),
+            info_hash_hex(&torrent)
+        );
+        assert_eq!(Ok(torrent["info"].sha1_bytes()), info_hash(&torrent));
+    }
+
+    #[test]
+    fn test_info_hash_failed() {
+        assert_eq!(
+            Err("missing key `info`".to_string()),
+            info_hash_hex(&parse_from_str("de").unwrap())
+        );
+        assert_eq!(
+            Err("`info` is not a dictionary".to_string()),
+            info_hash(&parse_from_str("d4:infoi1ee").unwrap())
+        );
+        assert!(info_hash(&BNode::Integer(1)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn test_ordered_torrent() {
+        let mut bnode = crate::parse_ordered(&mut DEBIAN_TORRENT.bytes()).unwrap();
+
+        bnode["info"]
+            .insert_entry("pieces".into(), BNode::Bytes(vec![0xab; 40]))
+            .unwrap();
+        assert_eq!(Ok(()), bnode.verify_torrent());
+        assert_eq!(
+            info_hash(&crate::parse_from_slice(&bnode.to_bytes()).unwrap()),
+            info_hash(&bnode)
+        );
+    }
+}
@@ -0,0 +1,605 @@
+use crate::prelude::*;
+use crate::{BDict, BNode};
+
+/// Number of nodes of each kind in a tree, see [`BNode::count_by_type`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NodeTypeCounts {
+    pub integers: usize,
+    pub bytes_nodes: usize,
+    /// The summed length of all byte strings, dictionary keys excluded.
+    pub bytes_total_len: usize,
+    pub lists: usize,
+    pub dicts: usize,
+}
+
+/// A rough share of a `BTreeMap` node per entry, inline key and value
+/// included.
+const BTREE_ENTRY_OVERHEAD: usize = 64;
+
+/// Pending work of [`BNode::map`], containers are rebuilt once all of their
+/// children have been transformed.
+enum MapFrame {
+    Enter(BNode),
+    List(usize),
+    Dict(Vec<String>),
+    #[cfg(feature = "indexmap")]
+    OrderedDict(Vec<String>),
+}
+
+impl BNode {
+    /// Visits every node in depth-first pre-order, parents before children.
+    pub fn walk<F>(&self, mut visitor: F)
+    where
+        F: FnMut(&BNode),
+    {
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            visitor(node);
+
+            match node {
+                BNode::List(list) => stack.extend(list.iter().rev()),
+                BNode::Dict(dict) => stack.extend(dict.values().rev()),
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => stack.extend(dict.values().rev()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Like [`BNode::walk`], children are visited after the visitor has
+    /// modified their parent.
+    pub fn walk_mut<F>(&mut self, mut visitor: F)
+    where
+        F: FnMut(&mut BNode),
+    {
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            visitor(node);
+
+            match node {
+                BNode::List(list) => stack.extend(list.iter_mut().rev()),
+                BNode::Dict(dict) => stack.extend(dict.values_mut().rev()),
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => stack.extend(dict.values_mut().rev()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Yields the integers and byte strings in depth-first pre-order.
+    pub fn leaves(&self) -> impl Iterator<Item = &BNode> + '_ {
+        let mut stack = vec![self];
+
+        core::iter::from_fn(move || {
+            while let Some(node) = stack.pop() {
+                match node {
+                    BNode::List(list) => stack.extend(list.iter().rev()),
+                    BNode::Dict(dict) => stack.extend(dict.values().rev()),
+                    #[cfg(feature = "indexmap")]
+                    BNode::OrderedDict(dict) => stack.extend(dict.values().rev()),
+                    _ => return Some(node),
+                }
+            }
+
+            None
+        })
+    }
+
+    /// Yields the values of all integers in depth-first pre-order.
+    pub fn select_integers(&self) -> impl Iterator<Item = i64> + '_ {
+        self.leaves().filter_map(|node| match node {
+            BNode::Integer(i) => Some(*i),
+            _ => None,
+        })
+    }
+
+    /// Yields the payloads of all byte strings in depth-first pre-order,
+    /// dictionary keys excluded.
+    pub fn select_bytes(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.leaves().filter_map(|node| match node {
+            BNode::Bytes(bytes) => Some(bytes.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Returns the maximum nesting level, `0` for integers and bytes and `1`
+    /// for an empty list or dictionary.
+    pub fn depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(self, 0)];
+
+        while let Some((node, level)) = stack.pop() {
+            match node {
+                BNode::List(list) => {
+                    max = max.max(level + 1);
+                    stack.extend(list.iter().map(|node| (node, level + 1)));
+                }
+                BNode::Dict(dict) => {
+                    max = max.max(level + 1);
+                    stack.extend(dict.values().map(|node| (node, level + 1)));
+                }
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => {
+                    max = max.max(level + 1);
+                    stack.extend(dict.values().map(|node| (node, level + 1)));
+                }
+                _ => {}
+            }
+        }
+
+        max
+    }
+
+    /// Accumulates `f` over every node in depth-first pre-order, parents
+    /// before children.
+    pub fn fold<A, F>(&self, init: A, f: F) -> A
+    where
+        F: Fn(A, &BNode) -> A + Clone,
+    {
+        let mut acc = init;
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            acc = f(acc, node);
+
+            match node {
+                BNode::List(list) => stack.extend(list.iter().rev()),
+                BNode::Dict(dict) => stack.extend(dict.values().rev()),
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => stack.extend(dict.values().rev()),
+                _ => {}
+            }
+        }
+
+        acc
+    }
+
+    /// Sums the length of all byte strings, dictionary keys excluded.
+    pub fn total_bytes_payload(&self) -> usize {
+        self.fold(0, |acc, node| match node {
+            BNode::Bytes(bytes) => acc + bytes.len(),
+            _ => acc,
+        })
+    }
+
+    /// Estimates the heap memory held by the tree, the node itself excluded.
+    /// Dictionary entries are counted at a rough 64 bytes of `BTreeMap`
+    /// overhead each, plus their key and value.
+    pub fn approximate_memory_bytes(&self) -> usize {
+        self.fold(0, |acc, node| {
+            acc + match node {
+                BNode::Integer(_) => 0,
+                // The boxed `BigInt` and its 64-bit digits.
+                #[cfg(feature = "bigint")]
+                BNode::BigInteger(i) => {
+                    core::mem::size_of::<num_bigint::BigInt>() + i.bits().div_ceil(64) as usize * 8
+                }
+                BNode::Bytes(bytes) => bytes.capacity(),
+                BNode::List(list) => list.capacity() * core::mem::size_of::<BNode>(),
+                BNode::Dict(dict) => dict
+                    .keys()
+                    .map(|key| BTREE_ENTRY_OVERHEAD + key.capacity())
+                    .sum(),
+                // Every entry is stored along with its hash and indexed by
+                // position in a hash table.
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => {
+                    dict.capacity()
+                        * (2 * core::mem::size_of::<usize>()
+                            + core::mem::size_of::<String>()
+                            + core::mem::size_of::<BNode>())
+                        + dict.keys().map(String::capacity).sum::<usize>()
+                }
+            }
+        })
+    }
+
+    /// Counts the nodes in the tree, the node itself included.
+    pub fn count_nodes(&self) -> usize {
+        self.fold(0, |acc, _| acc + 1)
+    }
+
+    /// Returns the greatest integer in the tree, `None` if there is none.
+    pub fn max_integer(&self) -> Option<i64> {
+        self.fold(None, |acc: Option<i64>, node| match node {
+            BNode::Integer(i) => Some(acc.map_or(*i, |max| max.max(*i))),
+            _ => acc,
+        })
+    }
+
+    pub fn count_by_type(&self) -> NodeTypeCounts {
+        let mut counts = NodeTypeCounts::default();
+
+        self.walk(|node| match node {
+            BNode::Integer(_) => counts.integers += 1,
+            #[cfg(feature = "bigint")]
+            BNode::BigInteger(_) => counts.integers += 1,
+            BNode::Bytes(bytes) => {
+                counts.bytes_nodes += 1;
+                counts.bytes_total_len += bytes.len();
+            }
+            BNode::List(_) => counts.lists += 1,
+            BNode::Dict(_) => counts.dicts += 1,
+            #[cfg(feature = "indexmap")]
+            BNode::OrderedDict(_) => counts.dicts += 1,
+        });
+
+        counts
+    }
+
+    /// Returns the first node in depth-first pre-order matching `predicate`.
+    pub fn find<F>(&self, predicate: F) -> Option<&BNode>
+    where
+        F: Fn(&BNode) -> bool,
+    {
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            if predicate(node) {
+                return Some(node);
+            }
+
+            match node {
+                BNode::List(list) => stack.extend(list.iter().rev()),
+                BNode::Dict(dict) => stack.extend(dict.values().rev()),
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => stack.extend(dict.values().rev()),
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// Returns all nodes matching `predicate` in depth-first pre-order.
+    pub fn find_all<F>(&self, predicate: F) -> Vec<&BNode>
+    where
+        F: Fn(&BNode) -> bool,
+    {
+        let mut found = vec![];
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            if predicate(node) {
+                found.push(node);
+            }
+
+            match node {
+                BNode::List(list) => stack.extend(list.iter().rev()),
+                BNode::Dict(dict) => stack.extend(dict.values().rev()),
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => stack.extend(dict.values().rev()),
+                _ => {}
+            }
+        }
+
+        found
+    }
+
+    /// Removes every node matching `predicate`, list elements are dropped and
+    /// dictionary entries are removed by their value. Returns `None` when the
+    /// node itself matches.
+    pub fn prune<F>(mut self, predicate: F) -> Option<BNode>
+    where
+        F: Fn(&BNode) -> bool + Clone,
+    {
+        if predicate(&self) {
+            return None;
+        }
+
+        let mut stack = vec![&mut self];
+
+        while let Some(node) = stack.pop() {
+            match node {
+                BNode::List(list) => {
+                    list.retain(|node| !predicate(node));
+                    stack.extend(list.iter_mut());
+                }
+                BNode::Dict(dict) => {
+                    dict.retain(|_, node| !predicate(node));
+                    stack.extend(dict.values_mut());
+                }
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(dict) => {
+                    dict.retain(|_, node| !predicate(node));
+                    stack.extend(dict.values_mut());
+                }
+                _ => {}
+            }
+        }
+
+        Some(self)
+    }
+
+    /// Applies `f` bottom-up to every node, containers receive the already
+    /// transformed children.
+    pub fn map<F>(self, f: F) -> BNode
+    where
+        F: Fn(BNode) -> BNode + Clone,
+    {
+        let mut frames = vec![MapFrame::Enter(self)];
+        let mut done: Vec<BNode> = vec![];
+
+        while let Some(frame) = frames.pop() {
+            match frame {
+                MapFrame::Enter(BNode::List(list)) => {
+                    frames.push(MapFrame::List(list.len()));
+                    frames.extend(list.into_iter().rev().map(MapFrame::Enter));
+                }
+                MapFrame::Enter(BNode::Dict(dict)) => {
+                    let (keys, values): (Vec<_>, Vec<_>) = dict.into_iter().unzip();
+                    frames.push(MapFrame::Dict(keys));
+                    frames.extend(values.into_iter().rev().map(MapFrame::Enter));
+                }
+                #[cfg(feature = "indexmap")]
+                MapFrame::Enter(BNode::OrderedDict(dict)) => {
+                    let (keys, values): (Vec<_>, Vec<_>) = dict.into_iter().unzip();
+                    frames.push(MapFrame::OrderedDict(keys));
+                    frames.extend(values.into_iter().rev().map(MapFrame::Enter));
+                }
+                MapFrame::Enter(node) => done.push(f(node)),
+                MapFrame::List(len) => {
+                    let list = done.split_off(done.len() - len);
+                    done.push(f(BNode::List(list)));
+                }
+                MapFrame::Dict(keys) => {
+                    let values = done.split_off(done.len() - keys.len());
+                    let dict: BDict = keys.into_iter().zip(values).collect();
+                    done.push(f(BNode::Dict(dict)));
+                }
+                #[cfg(feature = "indexmap")]
+                MapFrame::OrderedDict(keys) => {
+                    let values = done.split_off(done.len() - keys.len());
+                    let dict: crate::BOrderedDict = keys.into_iter().zip(values).collect();
+                    done.push(f(BNode::OrderedDict(dict)));
+                }
+            }
+        }
+
+        done.pop().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NodeTypeCounts;
+    use crate::tests::DEBIAN_TORRENT;
+    use crate::{parse_from_str, BNode};
+
+    #[test]
+    fn test_walk_order() {
+        let bnode = parse_from_str("d1:ali1ei2ee1:bd1:ci3eee").unwrap();
+
+        let mut visited = vec![];
+        bnode.walk(|node| visited.push(node.to_string()));
+        assert_eq!(
+            vec![
+                "d1:ali1ei2ee1:bd1:ci3eee",
+                "li1ei2ee",
+                "i1e",
+                "i2e",
+                "d1:ci3ee",
+                "i3e"
+            ],
+            visited
+        );
+
+        let mut count = 0;
+        BNode::Integer(1).walk(|_| count += 1);
+        assert_eq!(1, count);
+    }
+
+    #[test]
+    fn test_walk_mut() {
+        let mut bnode = parse_from_str("d1:ali1ei2ee1:bd1:ci3eee").unwrap();
+
+        bnode.walk_mut(|node| {
+            if let BNode::Integer(i) = node {
+                *i *= 10;
+            }
+        });
+        assert_eq!("d1:ali10ei20ee1:bd1:ci30eee", bnode.to_string());
+    }
+
+    #[test]
+    fn test_map_identity() {
+        let bnode = parse_from_str("d1:ali1ei2ee1:bd1:ci3ee1:d0:e").unwrap();
+        assert_eq!(bnode.clone(), bnode.map(|n| n));
+    }
+
+    #[test]
+    fn test_map_integers_and_bytes() {
+        let bnode = parse_from_str("d1:ali1e4:spamee").unwrap();
+
+        let doubled = bnode.clone().map(|n| match n {
+            BNode::Integer(i) => BNode::Integer(i * 2),
+            _ => n,
+        });
+        assert_eq!("d1:ali2e4:spamee", doubled.to_string());
+
+        let upper = bnode.map(|n| match n {
+            BNode::Bytes(bytes) => BNode::Bytes(bytes.to_ascii_uppercase()),
+            _ => n,
+        });
+        assert_eq!("d1:ali1e4:SPAMee", upper.to_string());
+    }
+
+    #[test]
+    fn test_map_nested_containers() {
+        let bnode = parse_from_str("lli1ei2eeli3eee").unwrap();
+
+        // Every list is replaced by its length after its children are mapped.
+        let lens = bnode.map(|n| match n {
+            BNode::List(list) => BNode::Integer(list.len() as i64),
+            _ => n,
+        });
+        assert_eq!(BNode::Integer(2), lens);
+    }
+
+    #[test]
+    fn test_prune() {
+        let bnode = parse_from_str("d1:ali1e4:spamli2e3:eggee1:b5:bacon1:ci3ee").unwrap();
+
+        assert_eq!(None, bnode.clone().prune(|n| n.is_dict()));
+        assert_eq!(
+            Some("d1:al4:spaml3:eggeee".to_string()),
+            bnode
+                .clone()
+                .prune(|n| n.is_integer() || n.len() > 4)
+                .map(|n| n.to_string())
+        );
+        assert_eq!(
+            Some("d1:ali1eli2eee1:ci3ee".to_string()),
+            bnode.clone().prune(BNode::is_bytes).map(|n| n.to_string())
+        );
+        assert_eq!(Some(bnode.clone()), bnode.clone().prune(|_| false));
+
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+        let stripped = torrent.prune(|n| n.is_bytes() && n.len() > 100).unwrap();
+        assert_eq!(3, stripped.count_by_type().bytes_nodes);
+    }
+
+    #[test]
+    fn test_find() {
+        let bnode = parse_from_str("d8:announce3:udp4:infod5:filesld3:url8:http://aeeee").unwrap();
+
+        let url = bnode.find(|n| n.as_str().map(|s| s.starts_with("http")).unwrap_or(false));
+        assert_eq!(Some(&BNode::from("http://a")), url);
+        assert_eq!(None, bnode.find(|n| n.is_integer()));
+    }
+
+    #[test]
+    fn test_find_all() {
+        let bnode = parse_from_str("li1eli2eli3eeed1:ai4eee").unwrap();
+
+        let integers = bnode.find_all(BNode::is_integer);
+        assert_eq!(
+            vec![
+                &BNode::Integer(1),
+                &BNode::Integer(2),
+                &BNode::Integer(3),
+                &BNode::Integer(4)
+            ],
+            integers
+        );
+        assert_eq!(3, bnode.find_all(|n| n.is_list()).len());
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(0, BNode::Integer(1).depth());
+        assert_eq!(0, BNode::from("spam").depth());
+        assert_eq!(1, BNode::List(vec![]).depth());
+        assert_eq!(2, parse_from_str("lli1ei2eei3ee").unwrap().depth());
+        assert!(parse_from_str(DEBIAN_TORRENT).unwrap().depth() >= 2);
+        assert_eq!(3, parse_from_str("d1:ad1:bleee").unwrap().depth());
+    }
+
+    #[test]
+    fn test_count_by_type() {
+        let bnode = parse_from_str("d1:ali1e2:bbe1:cdee").unwrap();
+
+        assert_eq!(
+            NodeTypeCounts {
+                integers: 1,
+                bytes_nodes: 1,
+                bytes_total_len: 2,
+                lists: 1,
+                dicts: 2,
+            },
+            bnode.count_by_type()
+        );
+        assert_eq!(0, BNode::Integer(1).total_bytes_payload());
+        assert_eq!(4, BNode::from("spam").total_bytes_payload());
+
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+        assert_eq!(41 + 35 + 145 + 145 + 31, torrent.total_bytes_payload());
+    }
+
+    #[test]
+    fn test_fold() {
+        let bnode = parse_from_str("d1:ali1ei-2ee1:b4:spam1:cd1:di7eee").unwrap();
+
+        let visited = bnode.fold(String::new(), |mut acc, node| {
+            acc.push_str(match node {
+                BNode::Integer(_) => "i",
+                BNode::Bytes(_) => "b",
+                BNode::List(_) => "l",
+                BNode::Dict(_) => "d",
+                #[cfg(feature = "bigint")]
+                BNode::BigInteger(_) => unreachable!(),
+                #[cfg(feature = "indexmap")]
+                BNode::OrderedDict(_) => unreachable!(),
+            });
+            acc
+        });
+        assert_eq!("dliibdi", visited);
+        assert_eq!(7, bnode.count_nodes());
+        assert_eq!(Some(7), bnode.max_integer());
+        assert_eq!(4, bnode.total_bytes_payload());
+
+        assert_eq!(None, BNode::from("spam").max_integer());
+        assert_eq!(Some(-1), BNode::Integer(-1).max_integer());
+
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+        assert_eq!(11, torrent.count_nodes());
+    }
+
+    #[test]
+    fn test_approximate_memory_bytes() {
+        assert_eq!(0, BNode::Integer(0).approximate_memory_bytes());
+        assert_eq!(1024, BNode::Bytes(vec![0; 1024]).approximate_memory_bytes());
+
+        let list = BNode::List(Vec::with_capacity(4));
+        assert_eq!(
+            4 * core::mem::size_of::<BNode>(),
+            list.approximate_memory_bytes()
+        );
+
+        let bnode = parse_from_str("d1:al4:spamee").unwrap();
+        let list_bytes = bnode["a"].approximate_memory_bytes();
+        assert!(list_bytes >= core::mem::size_of::<BNode>() + 4);
+        assert_eq!(64 + 1 + list_bytes, bnode.approximate_memory_bytes());
+
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+        assert!(torrent.approximate_memory_bytes() > torrent.total_bytes_payload());
+    }
+
+    #[test]
+    fn test_leaves() {
+        let bnode = parse_from_str("d1:ali1ei2ee1:bd1:c4:spame1:dlee").unwrap();
+
+        let leaves: Vec<_> = bnode.leaves().collect();
+        assert_eq!(
+            vec![&BNode::Integer(1), &BNode::Integer(2), &BNode::from("spam")],
+            leaves
+        );
+
+        let torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+        assert_eq!(5, torrent.leaves().filter(|n| n.is_bytes()).count());
+        assert!(torrent.leaves().all(|n| !n.is_list() && !n.is_dict()));
+        assert_eq!(1, BNode::Integer(1).leaves().count());
+    }
+
+    #[test]
+    fn test_select_leaves() {
+        let bnode = parse_from_str("d1:ali3el4:spami-1eee1:bd1:ci7e1:d2:hiee").unwrap();
+
+        assert_eq!(vec![3, -1, 7], bnode.select_integers().collect::<Vec<_>>());
+        assert_eq!(
+            vec![&b"spam"[..], b"hi"],
+            bnode.select_bytes().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(7), bnode.select_integers().max());
+        assert_eq!(0, BNode::from("spam").select_integers().count());
+
+        let mut torrent = parse_from_str(DEBIAN_TORRENT).unwrap();
+        torrent["info"]
+            .insert_entry("pieces".into(), BNode::Bytes(vec![0xab; 20]))
+            .unwrap();
+        assert_eq!(torrent.max_integer(), torrent.select_integers().max());
+        assert_eq!(1, torrent.select_bytes().filter(|b| b.len() == 20).count());
+    }
+}
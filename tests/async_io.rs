@@ -0,0 +1,83 @@
+#![cfg(feature = "tokio")]
+
+use bencodex::async_io::parse_async;
+use bencodex::{parse_from_slice, parse_partial, ErrorKind};
+use std::io::Cursor;
+use tokio::io::AsyncWriteExt;
+
+const DEBIAN_TORRENT: &[u8] = include_bytes!("fixtures/debian.torrent");
+
+#[tokio::test]
+async fn test_parse_async() {
+    let bnode = parse_async(Cursor::new(DEBIAN_TORRENT.to_vec()))
+        .await
+        .unwrap();
+
+    assert_eq!(parse_from_slice(DEBIAN_TORRENT).unwrap(), bnode);
+    assert_eq!(DEBIAN_TORRENT, &bnode.to_bytes()[..]);
+}
+
+#[tokio::test]
+async fn test_parse_async_larger_than_chunk() {
+    let raw = format!("{}:{}", 10_000, "x".repeat(10_000));
+    let bnode = parse_async(Cursor::new(raw.into_bytes())).await.unwrap();
+
+    assert_eq!(10_000, bnode.as_bytes().unwrap().len());
+}
+
+#[tokio::test]
+async fn test_parse_async_failed() {
+    let err = parse_async(Cursor::new(b"l4:spam".to_vec()))
+        .await
+        .unwrap_err();
+
+    assert_eq!(&ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[tokio::test]
+async fn test_parse_async_trailing_bytes() {
+    let bnode = parse_async(Cursor::new(b"i42ed3:fooi1ee".to_vec()))
+        .await
+        .unwrap();
+
+    assert_eq!(42, *bnode.as_integer().unwrap());
+}
+
+#[tokio::test]
+async fn test_parse_async_open_reader() {
+    let (mut writer, reader) = tokio::io::duplex(64);
+    writer.write_all(b"d3:fooli1ei2eee").await.unwrap();
+
+    // The writer stays open, the value alone tells where to stop.
+    let bnode = parse_async(reader).await.unwrap();
+    assert_eq!(parse_from_slice(b"d3:fooli1ei2eee").unwrap(), bnode);
+    drop(writer);
+}
+
+#[tokio::test]
+async fn test_parse_async_split_writes() {
+    let (mut writer, reader) = tokio::io::duplex(16);
+    let writing = tokio::spawn(async move {
+        writer.write_all(DEBIAN_TORRENT).await.unwrap();
+        writer
+    });
+
+    let bnode = parse_async(reader).await.unwrap();
+    assert_eq!(parse_from_slice(DEBIAN_TORRENT).unwrap(), bnode);
+    writing.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_parse_async_failed_position() {
+    for case in [&b"l4:spam"[..], b"li1e4:spa", b"d3:fooi1-ee", b"i1e"] {
+        let result = parse_async(Cursor::new(case.to_vec())).await;
+        match parse_partial(case) {
+            Ok((expected, _)) => assert_eq!(expected, result.unwrap()),
+            Err(expected) => {
+                let err = result.unwrap_err();
+                assert_eq!(expected.kind(), err.kind());
+                assert_eq!(expected.position, err.position);
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use bencodex::{bnode, BDict, BNode};
+
+#[test]
+fn test_bnode_scalars() {
+    assert_eq!(BNode::Integer(42), bnode!(42i64));
+    assert_eq!(BNode::Bytes(b"hello".to_vec()), bnode!("hello"));
+}
+
+#[test]
+fn test_bnode_list() {
+    assert_eq!(BNode::List(vec![]), bnode!([]));
+    assert_eq!(
+        BNode::List(vec![BNode::Integer(1), BNode::from("spam")]),
+        bnode!([1i64, "spam",])
+    );
+}
+
+#[test]
+fn test_bnode_nested_dict() {
+    let name = String::from("file.iso");
+    let node = bnode!({
+        "info" => bnode!({ "name" => name, "length" => 1024i64 }),
+        "list" => bnode!([bnode!([]), bnode!({})]),
+    });
+
+    assert_eq!(BNode::Dict(BDict::new()), bnode!({}));
+    assert_eq!(
+        b"d4:infod6:lengthi1024e4:name8:file.isoe4:listlledeee".to_vec(),
+        node.to_bytes()
+    );
+}
+
+mod hygiene {
+    // Neither `BNode` nor `BDict` is imported here, the macro must not
+    // depend on the caller's scope.
+    #[test]
+    fn test_bnode_without_imports() {
+        let dict = 1;
+        let node = bencodex::bnode!({ "dict" => bencodex::bnode!([dict as i64]) });
+        assert_eq!(b"d4:dictli1eee".to_vec(), node.to_bytes());
+    }
+}
@@ -0,0 +1,22 @@
+use bencodex::{Lexer, Token};
+
+#[test]
+fn test_public_lexer() {
+    let mut bytes = b"d3:fooli-42eee".iter().copied();
+    let mut lexer = Lexer::new(&mut bytes);
+
+    assert_eq!(Token::DictBegin, lexer.next_token().unwrap());
+    assert_eq!(Token::Length(3), lexer.look_ahead().unwrap());
+    assert_eq!(Token::Length(3), lexer.next_token().unwrap());
+    assert_eq!(Token::Colon, lexer.next_token().unwrap());
+    assert_eq!(b"foo".to_vec(), lexer.read_bytes(3).unwrap());
+    assert_eq!(5, lexer.position());
+
+    assert_eq!(Token::ListBegin, lexer.next_token().unwrap());
+    assert_eq!(Token::IntegerBegin, lexer.next_token().unwrap());
+    assert_eq!((-42, 2), lexer.read_i64_before(0, b'e').unwrap());
+    assert_eq!(Token::End, lexer.next_token().unwrap());
+    assert_eq!(Token::End, lexer.next_token().unwrap());
+    assert_eq!(Token::End, lexer.next_token().unwrap());
+    assert_eq!(Token::EOF, lexer.next_token().unwrap());
+}
@@ -0,0 +1,24 @@
+//! Uses the crate like a `no_std` dependent would, through `core` and
+//! `alloc` only.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec;
+use bencodex::{bnode, parse_from_slice, BNode};
+
+#[test]
+fn test_no_std_round_trip() {
+    let raw = b"d4:infod6:lengthi42e4:name8:file.isoe4:tagsl5:linuxee";
+    let bnode = parse_from_slice(raw).unwrap();
+
+    assert_eq!(raw.to_vec(), bnode.marshal_to_vec());
+    assert_eq!(
+        bnode!({
+            "info" => bnode!({ "length" => 42i64, "name" => "file.iso" }),
+            "tags" => bnode!(["linux"]),
+        }),
+        bnode
+    );
+    assert_eq!(BNode::List(vec![]), parse_from_slice(b"le").unwrap());
+}
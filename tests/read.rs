@@ -0,0 +1,82 @@
+use bencodex::{parse_from_read, parse_from_slice, BNode, BNodeStream, ErrorKind};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+
+const DEBIAN_TORRENT: &[u8] = include_bytes!("fixtures/debian.torrent");
+
+#[test]
+fn test_parse_from_cursor() {
+    let bnode = parse_from_read(Cursor::new(DEBIAN_TORRENT)).unwrap();
+    assert_eq!(parse_from_slice(DEBIAN_TORRENT).unwrap(), bnode);
+
+    assert!(parse_from_read(Cursor::new(b"d3:fooi1e")).is_err());
+}
+
+#[test]
+fn test_parse_from_buf_reader() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/debian.torrent");
+    let reader = BufReader::new(File::open(path).unwrap());
+
+    let bnode = parse_from_read(reader).unwrap();
+    assert_eq!(parse_from_slice(DEBIAN_TORRENT).unwrap(), bnode);
+}
+
+#[test]
+fn test_bnode_stream() {
+    let nodes: Vec<BNode> = BNodeStream::new(Cursor::new(b"i1ei-2ei3e"))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        vec![BNode::Integer(1), BNode::Integer(-2), BNode::Integer(3)],
+        nodes
+    );
+
+    let mut raw = b"i42e".to_vec();
+    raw.extend_from_slice(DEBIAN_TORRENT);
+    raw.extend_from_slice(b"d3:fooli1eee");
+    let mut stream = BNodeStream::new(Cursor::new(raw));
+    assert_eq!(BNode::Integer(42), stream.next().unwrap().unwrap());
+    assert_eq!(
+        parse_from_slice(DEBIAN_TORRENT).unwrap(),
+        stream.next().unwrap().unwrap()
+    );
+    assert_eq!("d3:fooli1eee", stream.next().unwrap().unwrap().to_string());
+    assert!(stream.next().is_none());
+
+    assert!(BNodeStream::new(Cursor::new(b"")).next().is_none());
+}
+
+#[test]
+fn test_bnode_stream_failed() {
+    let mut stream = BNodeStream::new(Cursor::new(b"i1ei2x"));
+    assert_eq!(BNode::Integer(1), stream.next().unwrap().unwrap());
+    let err = stream.next().unwrap().unwrap_err();
+    assert_eq!(&ErrorKind::InvalidToken(b'x'), err.kind());
+    assert_eq!(5, err.position);
+    assert!(stream.next().is_none());
+
+    let mut stream = BNodeStream::new(Cursor::new(b"i1el"));
+    assert!(stream.next().unwrap().is_ok());
+    assert_eq!(
+        &ErrorKind::UnexpectedEof,
+        stream.next().unwrap().unwrap_err().kind()
+    );
+}
+
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("broken pipe"))
+    }
+}
+
+#[test]
+fn test_bnode_stream_io_error() {
+    let mut stream = BNodeStream::new(Cursor::new(b"i1e").chain(FailingReader));
+    assert!(stream.next().unwrap().is_ok());
+
+    let err = stream.next().unwrap().unwrap_err();
+    assert_eq!(&ErrorKind::Io("broken pipe".to_string()), err.kind());
+    assert!(stream.next().is_none());
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "serde")]
+
+use bencodex::serde_format::{from_bencode, to_bencode};
+use bencodex::{parse_from_str, BNode};
+use serde::de::value::Error as ValueError;
+use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn test_serialize_to_json() {
+    let bnode = parse_from_str("d3:bar2:hi3:fooli1ei-2eee").unwrap();
+
+    let json = serde_json::to_value(&bnode).unwrap();
+    assert_eq!(
+        serde_json::json!({ "bar": [104, 105], "foo": [1, -2] }),
+        json
+    );
+}
+
+/// Claims far more elements than it has, like a forged length prefix.
+struct ForgedSeq(Option<i64>);
+
+impl<'de> SeqAccess<'de> for ForgedSeq {
+    type Error = ValueError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, ValueError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0
+            .take()
+            .map(|i| seed.deserialize(i.into_deserializer()))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(1 << 60)
+    }
+}
+
+struct ForgedList;
+
+impl<'de> Deserializer<'de> for ForgedList {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_seq(ForgedSeq(Some(1)))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+#[test]
+fn test_deserialize_forged_size_hint() {
+    let bnode = BNode::deserialize(ForgedList).unwrap();
+    assert_eq!(BNode::List(vec![BNode::Integer(1)]), bnode);
+}
+
+#[test]
+fn test_serialize_integer_to_json() {
+    assert_eq!("42", serde_json::to_string(&BNode::Integer(42)).unwrap());
+}
+
+#[test]
+fn test_deserialize_from_json() {
+    let json = serde_json::json!({ "foo": [1, -2], "bar": "hi" });
+
+    let bnode: BNode = serde_json::from_value(json).unwrap();
+    assert_eq!(parse_from_str("d3:bar2:hi3:fooli1ei-2eee").unwrap(), bnode);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct TorrentInfo {
+    name: String,
+    length: i64,
+    #[serde(rename = "piece length")]
+    piece_length: u32,
+    #[serde(with = "serde_bytes")]
+    pieces: Vec<u8>,
+    private: Option<bool>,
+    files: Vec<String>,
+}
+
+#[test]
+fn test_serde_format_round_trip() {
+    let raw =
+        b"d5:filesl1:a1:be6:lengthi1024e4:name8:file.iso12:piece lengthi256e6:pieces2:\xff\x00e";
+    let info: TorrentInfo = from_bencode(raw).unwrap();
+    assert_eq!(
+        TorrentInfo {
+            name: "file.iso".to_string(),
+            length: 1024,
+            piece_length: 256,
+            pieces: vec![0xff, 0x00],
+            private: None,
+            files: vec!["a".to_string(), "b".to_string()],
+        },
+        info
+    );
+
+    assert_eq!(raw.to_vec(), to_bencode(&info).unwrap());
+}
+
+#[test]
+fn test_serde_format_enum_and_bool() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Event {
+        Started,
+        Completed { downloaded: i64 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Announce {
+        compact: bool,
+        events: Vec<Event>,
+    }
+
+    let announce = Announce {
+        compact: true,
+        events: vec![Event::Started, Event::Completed { downloaded: 42 }],
+    };
+    let raw = to_bencode(&announce).unwrap();
+    assert_eq!(
+        b"d7:compacti1e6:eventsl7:Startedd9:Completedd10:downloadedi42eeeee".to_vec(),
+        raw
+    );
+    assert_eq!(announce, from_bencode(&raw).unwrap());
+}
+
+#[test]
+fn test_serde_format_errors() {
+    assert!(to_bencode(&1.5f64).is_err());
+    assert!(to_bencode(&u64::MAX).is_err());
+    assert!(from_bencode::<TorrentInfo>(b"d4:name1:ae").is_err());
+    assert!(from_bencode::<i64>(b"i1").is_err());
+
+    let err = to_bencode(&1.5f64).unwrap_err();
+    assert_eq!(bencodex::Error::NO_POSITION, err.position);
+    assert!(err.to_string().starts_with("bencode error: "));
+}
@@ -0,0 +1,48 @@
+use bencodex::{parse_from_str, BDict, BList, BNode};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn test_try_from_owned() -> Result<()> {
+    let x: i64 = parse_from_str("i42e")?.try_into()?;
+    assert_eq!(42, x);
+
+    let bytes: Vec<u8> = parse_from_str("4:spam")?.try_into()?;
+    assert_eq!(b"spam".to_vec(), bytes);
+
+    let string: String = parse_from_str("4:spam")?.try_into()?;
+    assert_eq!("spam", string);
+
+    let list: BList = parse_from_str("li1ei2ee")?.try_into()?;
+    assert_eq!(vec![BNode::Integer(1), BNode::Integer(2)], list);
+
+    let dict: BDict = parse_from_str("d3:fooi42ee")?.try_into()?;
+    assert_eq!(Some(&BNode::Integer(42)), dict.get("foo"));
+
+    Ok(())
+}
+
+#[test]
+fn test_try_from_borrowed() -> Result<()> {
+    let bnode = parse_from_str("d3:bar4:spam3:fooi42ee")?;
+    let dict: BDict = (&bnode).try_into()?;
+
+    let x: i64 = (&dict["foo"]).try_into()?;
+    assert_eq!(42, x);
+
+    let string: String = (&dict["bar"]).try_into()?;
+    assert_eq!("spam", string);
+
+    Ok(())
+}
+
+#[test]
+fn test_try_from_mismatch() {
+    let err = i64::try_from(BNode::from("spam")).unwrap_err();
+    assert_eq!("expected integer, found bytes", err);
+
+    let err = BList::try_from(&BNode::Integer(42)).unwrap_err();
+    assert_eq!("expected list, found integer", err);
+
+    assert!(String::try_from(BNode::Bytes(vec![0xff])).is_err());
+}